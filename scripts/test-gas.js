@@ -409,8 +409,7 @@ async function recordTestResults(client, contractAddress, signer, results) {
     // Collect tx hashes as proof
     const txHashes = validResults
         .map(result => result.txHash)
-        .filter(Boolean)
-        .join(',');
+        .filter(Boolean);
     
     // Create unique run ID with timestamp
     const runId = `run_${Date.now()}`;
@@ -422,7 +421,7 @@ async function recordTestResults(client, contractAddress, signer, results) {
             gas: totalGasUsed.toString(),
             avg_gas: avgGasPerByte.toString(),
             chain: config.CHAIN_ID,
-            tx_proof: txHashes || null
+            tx_proofs: txHashes.length > 0 ? txHashes : null
         }
     };
     
@@ -1,10 +1,13 @@
 use cosmwasm_std::{
-  entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-  to_json_binary, Addr, Uint128, StdError,
+  entry_point, Binary, Deps, DepsMut, Env, Event, MessageInfo, Response, StdResult,
+  to_json_binary, Addr, Coin, Decimal, Empty, Int128, Isqrt, Uint128, Uint256, StdError, Storage,
 };
-use cw_storage_plus::{Bound, Item, Map};
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::{Bound, Deque, Item, Map, PrimaryKey};
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
 use thiserror::Error;
 
 // Custom error type
@@ -33,6 +36,57 @@ pub enum ContractError {
     
     #[error("No data available")]
     NoData {},
+
+    #[error("Test run already exists: {run_id}")]
+    RunExists { run_id: String },
+
+    #[error("Test run not found: {run_id}")]
+    RunNotFound { run_id: String },
+
+    #[error("Limit exceeded: {requested} exceeds maximum of {max}")]
+    LimitExceeded { requested: u64, max: u64 },
+
+    #[error("Message not found: {id}")]
+    MessageNotFound { id: String },
+
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+
+    #[error("Storage full: {current} messages stored, maximum is {max}")]
+    StorageFull { current: u64, max: u64 },
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Confirmation must be \"{expected}\"")]
+    InvalidConfirmation { expected: String },
+
+    #[error("Invalid tx proof: {0}")]
+    InvalidTxProof(String),
+
+    #[error("Too many tx proofs: {count} exceeds maximum of {max}")]
+    TooManyProofs { count: u64, max: u64 },
+
+    #[error("Tx proofs too large: {size} bytes exceeds maximum of {max}")]
+    ProofTooLarge { size: u64, max: u64 },
+
+    #[error("Too many gas samples: {count} exceeds maximum of {max}")]
+    TooManySamples { count: u64, max: u64 },
+
+    #[error("Invalid gas price: {0}")]
+    InvalidGasPrice(String),
+
+    #[error("Invalid run metadata: {0}")]
+    InvalidRunMetadata(String),
+
+    #[error("Idempotency key is {length} bytes, maximum is {max}")]
+    IdempotencyKeyTooLong { length: u64, max: u64 },
+
+    #[error("Message count mismatch: run reports {count} messages but references {referenced}")]
+    MessageCountMismatch { count: u64, referenced: u64 },
+
+    #[error("Invalid migration: {0}")]
+    InvalidMigration(String),
 }
 
 // Contract state
@@ -41,6 +95,115 @@ pub struct State {
   pub owner: Addr,
   pub test_run_count: u64,
   pub last_test_timestamp: Option<u64>, // Use u64 instead of Timestamp for storage efficiency
+  // Number of entries in MESSAGES, kept in sync by save_message/remove_message
+  #[serde(default)]
+  pub message_count: u64,
+  // While set, every execute except SetPaused is rejected
+  #[serde(default)]
+  pub paused: bool,
+  // Addresses allowed to record test runs in addition to the owner
+  #[serde(default)]
+  pub recorders: Vec<Addr>,
+  // Once set, nobody passes owner or recorder checks again
+  #[serde(default)]
+  pub renounced: bool,
+  // Block height of instantiation, 0 for contracts created before this was tracked
+  #[serde(default)]
+  pub created_at_height: u64,
+  // Every message write since instantiation, never reset by ClearData
+  #[serde(default)]
+  pub lifetime_store_count: u64,
+  // Last sequence number given to a run, numbers of deleted runs aren't reused
+  #[serde(default)]
+  pub last_run_seq: u64,
+}
+
+impl State {
+  // Owner rights, permanently lost after renouncing
+  pub fn is_owner(&self, addr: &Addr) -> bool {
+      !self.renounced && *addr == self.owner
+  }
+  
+  // Owner or recorder rights for recording runs
+  pub fn can_record(&self, addr: &Addr) -> bool {
+      self.is_owner(addr) || (!self.renounced && self.recorders.contains(addr))
+  }
+}
+
+// Owner-tunable limits
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+  pub max_message_size: u64,
+  pub max_list_limit: u32, // Max page size for ListMessages
+  pub max_runs_limit: u32, // Max page size for GetTestRuns
+  #[serde(default)]
+  pub max_messages: Option<u64>, // Cap on stored messages, None for unlimited
+  #[serde(default)]
+  pub eviction: bool, // At the cap, evict the lowest id instead of failing
+  #[serde(default)]
+  pub hex_tx_proofs: bool, // Require every proof to be a 64 character hex tx hash
+  #[serde(default = "default_strict_validation")]
+  pub strict_validation: bool, // Reject runs whose count, gas and avg_gas disagree
+  #[serde(default = "default_regression_threshold_bps")]
+  pub regression_threshold_bps: u64, // Gas increase, in basis points, that CompareRuns flags
+  #[serde(default)]
+  pub min_gas_per_byte: Option<Uint128>, // Reject runs with a lower avg_gas, None for no bound
+  #[serde(default)]
+  pub max_gas_per_byte: Option<Uint128>, // Reject runs with a higher avg_gas, None for no bound
+  // Max drift of a supplied avg_gas from gas / bytes, configs stored without one get the default
+  #[serde(default = "default_avg_gas_tolerance_bps", deserialize_with = "deserialize_avg_gas_tolerance")]
+  pub avg_gas_tolerance_bps: u64,
+  #[serde(default)]
+  pub open_recording: bool, // Let any sender record test runs, for private test chains
+  #[serde(default = "default_max_proof_bytes")]
+  pub max_proof_bytes: u64, // Cap on the combined length of a run's tx proofs
+  #[serde(default)]
+  pub gas_budget: Option<Uint128>, // Cumulative recorded gas that RecordTestRun warns past, None for no budget
+}
+
+fn default_strict_validation() -> bool {
+  true
+}
+
+fn default_regression_threshold_bps() -> u64 {
+  DEFAULT_REGRESSION_THRESHOLD_BPS
+}
+
+fn default_max_proof_bytes() -> u64 {
+  DEFAULT_MAX_PROOF_BYTES
+}
+
+fn default_avg_gas_tolerance_bps() -> u64 {
+  DEFAULT_AVG_GAS_TOLERANCE_BPS
+}
+
+// Earlier configs stored the tolerance as an optional value, null meaning no check
+fn deserialize_avg_gas_tolerance<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  Ok(Option::<u64>::deserialize(deserializer)?.unwrap_or(DEFAULT_AVG_GAS_TOLERANCE_BPS))
+}
+
+impl Default for Config {
+  fn default() -> Self {
+      Config {
+          max_message_size: MAX_MESSAGE_SIZE,
+          max_list_limit: DEFAULT_MAX_LIST_LIMIT,
+          max_runs_limit: DEFAULT_MAX_RUNS_LIMIT,
+          max_messages: None,
+          eviction: false,
+          hex_tx_proofs: false,
+          strict_validation: true,
+          regression_threshold_bps: DEFAULT_REGRESSION_THRESHOLD_BPS,
+          min_gas_per_byte: None,
+          max_gas_per_byte: None,
+          avg_gas_tolerance_bps: DEFAULT_AVG_GAS_TOLERANCE_BPS,
+          open_recording: false,
+          max_proof_bytes: DEFAULT_MAX_PROOF_BYTES,
+          gas_budget: None,
+      }
+  }
 }
 
 // Compact storage for messages with minimal overhead
@@ -50,6 +213,54 @@ pub struct StoredMessage {
   pub length: u64,
   // Only store timestamps as seconds (u64) instead of full Timestamp objects
   pub stored_at: u64,
+  // Block height of the store, 0 for messages stored before this was tracked
+  #[serde(default)]
+  pub stored_at_height: u64,
+  // Gas observed when the message was stored, if the sender measured it
+  #[serde(default)]
+  pub gas_used: Option<Uint128>,
+  // Hidden from ListMessages by default but kept for audit
+  #[serde(default)]
+  pub archived: bool,
+  // content holds the base64 of the run-length encoded payload, see decoded_content
+  #[serde(default)]
+  pub compressed: bool,
+}
+
+impl StoredMessage {
+  // The payload as submitted, undoing compression
+  pub fn decoded_content(&self) -> StdResult<String> {
+      if !self.compressed {
+          return Ok(self.content.clone());
+      }
+      let decoded = rle_decode(&Binary::from_base64(&self.content)?)?;
+      String::from_utf8(decoded).map_err(|err| StdError::generic_err(err.to_string()))
+  }
+}
+
+// Byte-level run-length encoding as (run length, byte) pairs, runs capped at 255
+fn rle_encode(data: &[u8]) -> Vec<u8> {
+  let mut encoded = Vec::new();
+  let mut bytes = data.iter().peekable();
+  while let Some(&byte) = bytes.next() {
+      let mut run = 1u8;
+      while run < u8::MAX && bytes.peek() == Some(&&byte) {
+          bytes.next();
+          run += 1;
+      }
+      encoded.extend([run, byte]);
+  }
+  encoded
+}
+
+fn rle_decode(data: &[u8]) -> StdResult<Vec<u8>> {
+  if data.len() % 2 != 0 {
+      return Err(StdError::generic_err("corrupt compressed message"));
+  }
+  Ok(data
+      .chunks_exact(2)
+      .flat_map(|pair| std::iter::repeat(pair[1]).take(pair[0] as usize))
+      .collect())
 }
 
 // Compact storage for test run data 
@@ -60,15 +271,270 @@ pub struct TestRunStats {
   pub message_count: u64, 
   pub total_gas: Uint128,
   pub avg_gas_per_byte: Uint128,
+  // Trimmed and lowercased so differently typed ids aggregate together
   pub chain_id: String,
-  // Store tx hashes in a space-efficient format - comma separated
-  pub tx_proof: Option<String>, // Optional field for tx hash proofs
+  // Chain id as submitted, only kept when normalization changed it
+  #[serde(default)]
+  pub raw_chain_id: Option<String>,
+  // Tx hash proofs, older runs stored them as one comma separated string
+  #[serde(default, alias = "tx_proof", deserialize_with = "deserialize_proofs")]
+  pub tx_proofs: Vec<String>,
+  // Message length the run was measured at (None for mixed-size runs)
+  #[serde(default)]
+  pub byte_length: Option<u64>,
+  // Self-reported wasm code size of the contract under test
+  #[serde(default)]
+  pub code_size_bytes: Option<u64>,
+  // Total payload bytes across the run's messages, 0 when not reported
+  #[serde(default)]
+  pub total_bytes: u64,
+  // Per-message gas distribution, None when not reported
+  #[serde(default)]
+  pub min_gas: Option<Uint128>,
+  #[serde(default)]
+  pub max_gas: Option<Uint128>,
+  #[serde(default)]
+  pub median_gas: Option<Uint128>,
+  // Raw (bytes, gas) table from the sweep, in submission order
+  #[serde(default)]
+  pub samples: Vec<GasSample>,
+  // Gas price paid during the run, always set together with fee_denom
+  #[serde(default)]
+  pub gas_price: Option<Decimal>,
+  #[serde(default)]
+  pub fee_denom: Option<String>,
+  // Free-form labels such as the binary version under test
+  #[serde(default)]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  pub note: Option<String>,
+  // Assigned by save_test_run in recording order, 0 until migrate numbers older runs
+  #[serde(default)]
+  pub seq: u64,
+  // Wall-clock span of the sweep in seconds since epoch, as reported by the client
+  #[serde(default)]
+  pub started_at: Option<u64>,
+  #[serde(default)]
+  pub finished_at: Option<u64>,
+  // Chain software version reported by the client, e.g. "wasmd v0.50.0"
+  #[serde(default)]
+  pub node_version: Option<String>,
+  // cw2 version of this contract when the run was recorded, None for older runs
+  #[serde(default)]
+  pub contract_version: Option<String>,
+  // Stored messages the run claimed to cover, verified at record time
+  #[serde(default)]
+  pub coverage: Option<MessageCoverage>,
 }
 
-// Initialize message (minimal required data)
+impl TestRunStats {
+  // Seconds the sweep took, None unless both ends were reported
+  fn duration_secs(&self) -> Option<u64> {
+      Some(self.finished_at?.saturating_sub(self.started_at?))
+  }
+}
+
+// Count and total length of the stored messages a run references
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MessageCoverage {
+  pub messages: u64,
+  pub bytes: u64,
+}
+
+// One measured message size and its gas cost
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GasSample {
+  pub bytes: u64,
+  pub gas: Uint128,
+}
+
+// Proofs are either a list or a legacy comma separated string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProofList {
+  List(Vec<String>),
+  Joined(String),
+}
+
+impl From<ProofList> for Vec<String> {
+  fn from(proofs: ProofList) -> Self {
+      match proofs {
+          ProofList::List(proofs) => proofs,
+          ProofList::Joined(joined) => split_proofs(&joined),
+      }
+  }
+}
+
+fn deserialize_proofs<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  Ok(deserialize_optional_proofs(deserializer)?.unwrap_or_default())
+}
+
+fn deserialize_optional_proofs<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  Ok(Option::<ProofList>::deserialize(deserializer)?.map(Vec::from))
+}
+
+// Totals are either a Uint128 string or a legacy u64 number
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WideTotal {
+  Wide(Uint128),
+  Legacy(u64),
+}
+
+fn deserialize_wide_total<'de, D>(deserializer: D) -> Result<Uint128, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  Ok(match WideTotal::deserialize(deserializer)? {
+      WideTotal::Wide(total) => total,
+      WideTotal::Legacy(total) => total.into(),
+  })
+}
+
+// Split a comma separated proof string, skipping empty segments
+fn split_proofs(joined: &str) -> Vec<String> {
+  joined
+      .split(',')
+      .map(str::trim)
+      .filter(|proof| !proof.is_empty())
+      .map(String::from)
+      .collect()
+}
+
+// Summary of a single clear operation
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClearEvent {
+  pub timestamp: u64,
+  pub messages_removed: u64,
+  pub runs_removed: u64,
+  pub bytes_freed: u64,
+}
+
+// Running sums over stored runs, updated on every run write and delete
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Aggregates {
+  pub run_count: u64,
+  pub total_messages: u64,
+  pub total_gas: Uint128,
+  // Older contracts stored this as a u64 number
+  #[serde(deserialize_with = "deserialize_wide_total")]
+  pub total_bytes: Uint128,
+  // Products of two Uint128 values, widened so the sums don't saturate
+  pub gas_squares: Uint256, // Sum of squared per-run total gas, for the std dev
+  pub weighted_gas: Uint256, // Sum of avg_gas_per_byte * bytes per run
+  #[serde(default)]
+  pub timed_messages: u64, // Messages of runs with a nonzero duration
+  #[serde(default)]
+  pub timed_secs: u64, // Summed duration of those runs
+  #[serde(default)]
+  pub runs_without_bytes: u64, // Runs with neither reported bytes nor an avg_gas to estimate them
+  #[serde(default)]
+  pub runs_estimated_bytes: u64, // Runs whose bytes were estimated from avg_gas instead of reported
+}
+
+impl Aggregates {
+  // Fails instead of wrapping or panicking when a total leaves its range
+  fn add(&mut self, run: &TestRunStats) -> StdResult<()> {
+      let overflow = |field: &str| StdError::generic_err(format!("gas summary overflow: {} is out of range", field));
+      let bytes = run_bytes(run);
+      let gas = Uint256::from(run.total_gas);
+      self.run_count = self.run_count.checked_add(1).ok_or_else(|| overflow("run_count"))?;
+      self.total_messages = self.total_messages.checked_add(run.message_count).ok_or_else(|| overflow("total_messages"))?;
+      self.total_gas = self.total_gas.checked_add(run.total_gas).map_err(|_| overflow("total_gas"))?;
+      self.total_bytes = self.total_bytes.checked_add(bytes.into()).map_err(|_| overflow("total_bytes"))?;
+      self.gas_squares = self.gas_squares.checked_add(gas * gas).map_err(|_| overflow("gas_squares"))?;
+      self.weighted_gas = self
+          .weighted_gas
+          .checked_add(Uint256::from(run.avg_gas_per_byte) * Uint256::from(bytes))
+          .map_err(|_| overflow("weighted_gas"))?;
+      if bytes == 0 {
+          self.runs_without_bytes = self.runs_without_bytes.checked_add(1).ok_or_else(|| overflow("runs_without_bytes"))?;
+      }
+      if bytes > 0 && run.total_bytes == 0 {
+          self.runs_estimated_bytes = self.runs_estimated_bytes.checked_add(1).ok_or_else(|| overflow("runs_estimated_bytes"))?;
+      }
+      if let Some(duration) = run.duration_secs().filter(|duration| *duration > 0) {
+          self.timed_messages = self.timed_messages.checked_add(run.message_count).ok_or_else(|| overflow("timed_messages"))?;
+          self.timed_secs = self.timed_secs.checked_add(duration).ok_or_else(|| overflow("timed_secs"))?;
+      }
+      Ok(())
+  }
+  
+  fn remove(&mut self, run: &TestRunStats) {
+      let bytes = run_bytes(run);
+      self.run_count = self.run_count.saturating_sub(1);
+      self.total_messages = self.total_messages.saturating_sub(run.message_count);
+      self.total_gas = self.total_gas.saturating_sub(run.total_gas);
+      self.total_bytes = self.total_bytes.saturating_sub(bytes.into());
+      let gas = Uint256::from(run.total_gas);
+      self.gas_squares = self.gas_squares.saturating_sub(gas * gas);
+      self.weighted_gas = self.weighted_gas.saturating_sub(Uint256::from(run.avg_gas_per_byte) * Uint256::from(bytes));
+      if bytes == 0 {
+          self.runs_without_bytes = self.runs_without_bytes.saturating_sub(1);
+      }
+      if bytes > 0 && run.total_bytes == 0 {
+          self.runs_estimated_bytes = self.runs_estimated_bytes.saturating_sub(1);
+      }
+      if let Some(duration) = run.duration_secs().filter(|duration| *duration > 0) {
+          self.timed_messages = self.timed_messages.saturating_sub(run.message_count);
+          self.timed_secs = self.timed_secs.saturating_sub(duration);
+      }
+  }
+}
+
+// Initialize message (minimal required data)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-  // Only required fields
+  // Owner address, defaults to the instantiating sender
+  pub owner: Option<String>,
+  // Cap on stored messages, unlimited when not set
+  pub max_messages: Option<u64>,
+}
+
+// One run of a RecordTestRunBatch, same fields and defaults as RecordTestRun
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecordEntry {
+  pub run_id: String,
+  pub count: u64,
+  pub gas: Uint128,
+  #[serde(default)]
+  pub avg_gas: Option<Uint128>,
+  pub chain: String,
+  #[serde(default, alias = "tx_proof", deserialize_with = "deserialize_optional_proofs")]
+  pub tx_proofs: Option<Vec<String>>,
+  #[serde(default)]
+  pub tx_hashes: Option<Vec<String>>,
+  pub byte_length: Option<u64>,
+  pub overwrite: Option<bool>,
+  pub code_size_bytes: Option<u64>,
+  #[serde(default)]
+  pub bytes: u64,
+  pub min_gas: Option<Uint128>,
+  pub max_gas: Option<Uint128>,
+  pub median_gas: Option<Uint128>,
+  #[serde(default)]
+  pub samples: Vec<GasSample>,
+  pub gas_price: Option<Decimal>,
+  pub fee_denom: Option<String>,
+  pub tags: Option<Vec<String>>,
+  pub note: Option<String>,
+  pub started_at: Option<u64>,
+  pub finished_at: Option<u64>,
+  pub node_version: Option<String>,
+  pub message_ids: Option<Vec<String>>,
+}
+
+// Migrate message, `limit` caps the legacy runs moved per call (default DEFAULT_MIGRATE_LIMIT)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+  #[serde(default)]
+  pub limit: Option<u32>,
 }
 
 // Execute messages with optimized parameter names
@@ -76,28 +542,186 @@ pub struct InstantiateMsg {
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
   // Store a message of any length
-  StoreMessage { content: String },
+  // A repeated idempotency_key returns the originally stored id instead of writing again
+  // gas_used optionally records the measured cost alongside the payload
+  // dedup returns the id of an earlier dedup store with identical content instead of writing
+  // compress run-length encodes the content when that makes it smaller
+  StoreMessage {
+      content: String,
+      idempotency_key: Option<String>,
+      gas_used: Option<Uint128>,
+      dedup: Option<bool>,
+      compress: Option<bool>,
+  },
   
   // Store a message with a specific target length
   // If content is longer than length, it will be truncated
   // If content is shorter than length, it will be padded with spaces
-  StoreFixedLength { content: String, length: u64 },
+  StoreFixedLength { content: String, length: u64, idempotency_key: Option<String> },
   
   // Record aggregated test run data with transaction proofs
   RecordTestRun {
       run_id: String,
       count: u64,           // message_count shortened
       gas: Uint128,         // total_gas_used shortened
-      avg_gas: Uint128,     // average_gas_per_byte shortened
+      // average_gas_per_byte shortened. It is gas per byte, not per message, so it is
+      // derived as gas / bytes (or gas / (count * byte_length)) when omitted, and a
+      // supplied value must be within avg_gas_tolerance_bps of that
+      #[serde(default)]
+      avg_gas: Option<Uint128>,
       chain: String,        // chain_id shortened
-      tx_proof: Option<String>, // tx_hashes renamed for clarity
+      // Tx hash proofs, the old comma separated tx_proof string is still accepted
+      #[serde(default, alias = "tx_proof", deserialize_with = "deserialize_optional_proofs")]
+      tx_proofs: Option<Vec<String>>,
+      // Same as tx_proofs, takes precedence when both are given
+      #[serde(default)]
+      tx_hashes: Option<Vec<String>>,
+      byte_length: Option<u64>, // message length the run measured, feeds GetGasCurve
+      overwrite: Option<bool>,  // replace an existing run_id instead of rejecting it
+      code_size_bytes: Option<u64>, // wasm size of the contract under test
+      #[serde(default)]
+      bytes: u64,               // total payload bytes across the run, 0 if unknown
+      min_gas: Option<Uint128>,    // cheapest single message in the run
+      max_gas: Option<Uint128>,    // most expensive single message in the run
+      median_gas: Option<Uint128>, // median per-message gas
+      #[serde(default)]
+      samples: Vec<GasSample>,     // per-size measurements, at most MAX_GAS_SAMPLES
+      gas_price: Option<Decimal>,  // price per gas unit paid, requires fee_denom
+      fee_denom: Option<String>,   // denom the fee was paid in, requires gas_price
+      tags: Option<Vec<String>>,   // e.g. "wasmd v0.50", at most MAX_RUN_TAGS
+      note: Option<String>,        // free-form, at most MAX_RUN_NOTE_LENGTH bytes
+      started_at: Option<u64>,     // sweep start in seconds since epoch
+      finished_at: Option<u64>,    // sweep end, not before started_at or after the block time
+      node_version: Option<String>, // e.g. "wasmd v0.50.0", at most MAX_NODE_VERSION_LENGTH bytes
+      // Stored messages the run covers, each must exist and their number must equal count
+      message_ids: Option<Vec<String>>,
+  },
+  
+  // Import up to MAX_RECORD_BATCH runs at once, e.g. history from another contract
+  // Any invalid entry fails the whole batch
+  RecordTestRunBatch { runs: Vec<RecordEntry> },
+  
+  // Replace the data of an existing run without counting it as a new run
+  UpdateTestRun {
+      run_id: String,
+      count: u64,
+      gas: Uint128,
+      #[serde(default)]
+      avg_gas: Option<Uint128>, // checked and derived as in RecordTestRun
+      chain: String,
+      #[serde(default, alias = "tx_proof", deserialize_with = "deserialize_optional_proofs")]
+      tx_proofs: Option<Vec<String>>,
+      #[serde(default)]
+      tx_hashes: Option<Vec<String>>,
+      byte_length: Option<u64>,
+      code_size_bytes: Option<u64>,
+      #[serde(default)]
+      bytes: u64,
+      min_gas: Option<Uint128>,
+      max_gas: Option<Uint128>,
+      median_gas: Option<Uint128>,
+      #[serde(default)]
+      samples: Vec<GasSample>,
+      gas_price: Option<Decimal>,
+      fee_denom: Option<String>,
   },
   
   // Clear old test data (admin only)
-  ClearData {},
+  // With a limit, at most that many entries per map are removed per call, repeat until complete
+  ClearData { limit: Option<u32> },
+  
+  // Update size and pagination limits (admin only), None keeps the current value
+  // max_messages of 0 removes the cap, eviction turns the cap into a rolling window
+  // A gas per byte bound of 0 removes that bound
+  UpdateConfig {
+      max_message_size: Option<u64>,
+      max_list_limit: Option<u32>,
+      max_runs_limit: Option<u32>,
+      max_messages: Option<u64>,
+      eviction: Option<bool>,
+      hex_tx_proofs: Option<bool>,
+      strict_validation: Option<bool>,
+      regression_threshold_bps: Option<u64>,
+      min_gas_per_byte: Option<Uint128>,
+      max_gas_per_byte: Option<Uint128>,
+      avg_gas_tolerance_bps: Option<u64>, // between 1 and MAX_AVG_GAS_TOLERANCE_BPS
+      open_recording: Option<bool>,
+      max_proof_bytes: Option<u64>,
+      gas_budget: Option<Uint128>, // 0 removes the budget
+  },
+  
+  // Store a message then load it back `reads` times in the same execution
+  // Comparing gas across read counts gives the warm read cost
+  StoreAndRead { content: String, reads: u32 },
+  
+  // Load previously stored messages without writing (cold read cost)
+  ReadMessages { ids: Vec<String> },
+  
+  // Range over up to `limit` stored messages without writing (iterator cost)
+  IterateMessages { limit: u32, ascending: bool },
+  
+  // Increment a named counter (read-modify-write cost)
+  IncrementCounter { name: String },
+  
+  // Reset a named counter to zero (admin only)
+  ResetCounter { name: String },
+  
+  // Restart run numbering without deleting runs or messages (admin only)
+  ResetCounters {},
+  
+  // Delete up to `limit` messages in key order after `start_after` (admin only)
+  DeleteRange { start_after: Option<String>, limit: u32 },
+  
+  // Pause or resume all other executes (admin only)
+  SetPaused { paused: bool },
+  
+  // Grant or revoke RecordTestRun access (admin only)
+  UpdateRecorders {
+      #[serde(default)]
+      add: Vec<String>,
+      #[serde(default)]
+      remove: Vec<String>,
+  },
+  
+  // Permanently give up owner rights, `confirm` must be "renounce" (admin only)
+  RenounceOwnership { confirm: String },
+  
+  // Delete only messages or only test runs, optionally those whose id starts with `prefix` (admin only)
+  ClearMessages { prefix: Option<String>, limit: Option<u32> },
+  ClearTestRuns { prefix: Option<String>, limit: Option<u32> },
+  
+  // Remove a single mis-recorded run (admin only)
+  // chain is only needed when the run id was recorded on several chains
+  DeleteTestRun { run_id: String, chain: Option<String> },
+  
+  // Replace the content of an existing message in place (admin only)
+  UpdateMessage { id: String, content: String },
+  
+  // Extend a stored message with `suffix` to measure incremental growth (admin only)
+  AppendToMessage { id: String, suffix: String },
+  
+  // Mark a message inactive, or active again, without deleting it (admin only)
+  ArchiveMessage { id: String, archived: bool },
+  
+  // Add tx hash proofs to an already recorded run (owner or recorders)
+  AppendTxProofs { run_id: String, proofs: Vec<String>, chain: Option<String> },
+}
+
+// Key GetTestRuns pages through
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunSort {
+  // Lexical run id, an id recorded on several chains stays on one page
+  #[default]
+  ById,
+  // Recording order by run seq, so "desc" is newest-first and start_after is a seq
+  BySeq,
 }
 
-// Query messages
+/// Query messages
+///
+/// Together with the response types this is the stable interface for other
+/// contracts, which send it as a `WasmQuery::Smart`, e.g. `GetGasSummary` for a `GasSummary`
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
@@ -105,13 +729,101 @@ pub enum QueryMsg {
   GetMessage { id: String },
   ListMessages { 
       start_after: Option<String>,
+      end_before: Option<String>,
       limit: Option<u32>,
+      include_archived: Option<bool>, // archived messages are skipped unless set
   },
   GetTestRuns {
       start_after: Option<String>,
       limit: Option<u32>,
+      chain: Option<String>, // only runs on this chain, the limit counts matches
+      order: Option<String>,  // "asc" or "desc" along sort, "desc" by default
+      sort: Option<RunSort>,  // by_id unless set
+      tag: Option<String>,    // only runs carrying this exact tag
+      node_version: Option<String>, // only runs recorded with this exact node_version
+      include_proofs: Option<bool>, // adds each run's tx proofs, limit at most MAX_PROOF_PAGE_LIMIT
   },
-  GetGasSummary {},
+  // include_proofs adds the run's tx proof list to the response, chain is
+  // only needed when the id was recorded on more than one chain
+  GetTestRun { run_id: String, include_proofs: Option<bool>, chain: Option<String> },
+  // Most expensive runs by total_gas, highest first
+  TopRunsByGas { limit: Option<u32> },
+  // Page through a run's samples, start_after is the index of the last sample seen
+  // chain is only needed when the id was recorded on more than one chain
+  GetRunSamples { run_id: String, start_after: Option<u32>, limit: Option<u32>, chain: Option<String> },
+  // Config, gas summary and the latest runs in one call
+  GetDashboard { runs_limit: Option<u32> },
+  // Fit gas = intercept + slope * bytes over one run's samples, or every run's
+  GetGasModel { run_id: Option<String> },
+  // Predict the gas of storing `bytes` from recorded runs, optionally on one chain
+  EstimateGas { bytes: u64, chain: Option<String> },
+  // Whether `address` currently holds owner rights, for showing admin controls
+  IsOwner { address: String },
+  // EstimateGas priced at the chain's most recently recorded gas price
+  EstimateFee { bytes: u64, chain: Option<String> },
+  // Change in gas from run `base` to the newer run `against`, both looked up on
+  // `chain` when given, which is needed for ids recorded on more than one chain
+  CompareRuns { base: String, against: String, chain: Option<String> },
+  // CompareRuns between the two most recent runs on `chain`
+  GetLatestDelta { chain: String },
+  // Optionally restricted to one chain, matched after normalization
+  // window limits the std dev and percentiles to the newest runs, all runs when not set
+  GetGasSummary {
+      #[serde(default)]
+      chain: Option<String>,
+      #[serde(default)]
+      window: Option<u32>,
+  },
+  // GetGasSummary recomputed from a full scan, for checking the cached aggregates
+  GetGasSummaryExact {},
+  // GetGasSummary with gas_per_byte compared to a caller-supplied baseline
+  GetGasSummaryRelative { baseline_gas_per_byte: Uint128 },
+  GetGasSummaryByChain {},
+  // Distinct chain ids across all runs, sorted
+  ListChains {},
+  // One chain's runs in run_id order, start_after is exclusive
+  GetChainRuns { chain: String, start_after: Option<String>, limit: Option<u32> },
+  GetGasCurve {},
+  GetMessageBlockSpan {},
+  GetGasVsCodeSize {},
+  GetHealth {},
+  // Liveness probe, only loads the state
+  Health {},
+  // Newest run by timestamp, optionally on one chain, NoData when there is none
+  GetLatestRun { chain: Option<String> },
+  // A run by the sequence number it was given when first recorded
+  GetRunBySeq { seq: u64 },
+  // Runs with from <= timestamp <= to in time order, oldest first unless ascending is false
+  GetRunsByTime { from: Option<u64>, to: Option<u64>, limit: Option<u32>, ascending: Option<bool> },
+  // Timestamps of the oldest and newest run, None for both without runs
+  GetRunTimeBounds {},
+  // (time, gas_per_byte, chain) of runs with from <= timestamp <= to, oldest first
+  // Runs with a zero avg_gas are left out, pass next back as start_after to continue
+  GetGasSeries {
+      chain: Option<String>,
+      from: Option<u64>,
+      to: Option<u64>,
+      limit: Option<u32>,
+      start_after: Option<SeriesCursor>,
+  },
+  // Stored messages counted in bucket_size-wide length bins, at most max_scan messages read
+  GetLengthHistogram { bucket_size: u64, max_scan: Option<u32> },
+  GetCounter { name: String },
+  GetVersion {},
+  // Split a run's gas into GetGasModel overhead and payload, chain as in GetTestRun
+  DecomposeRun { run_id: String, chain: Option<String> },
+  GetStorageStats {},
+  ListRecorders {},
+  ValidateMessage { content: String },
+  ListMessagesSince { since: u64, limit: Option<u32> },
+  // Contents of the first messages in id order joined by separator (none by default),
+  // limit is clamped like ListMessages
+  ConcatMessages { separator: Option<String>, limit: Option<u32> },
+  // Messages whose id starts with prefix, e.g. "msg_12" for heights 12, 120-129, ...
+  ListMessagesByPrefix { prefix: String, limit: Option<u32> },
+  GetClearHistory {},
+  // One GetTestRuns page (ascending by run id) as CSV with a header row
+  ExportRunsCsv { start_after: Option<String>, limit: Option<u32> },
 }
 
 // Response types
@@ -120,6 +832,32 @@ pub struct ConfigResponse {
   pub owner: String,
   pub test_count: u64,
   pub last_test: Option<u64>,
+  pub max_message_size: u64,
+  pub max_list_limit: u32,
+  pub max_runs_limit: u32,
+  pub max_messages: Option<u64>,
+  pub eviction: bool,
+  pub hex_tx_proofs: bool,
+  pub strict_validation: bool,
+  pub regression_threshold_bps: u64,
+  pub min_gas_per_byte: Option<Uint128>,
+  pub max_gas_per_byte: Option<Uint128>,
+  pub avg_gas_tolerance_bps: u64,
+  pub open_recording: bool,
+  pub max_proof_bytes: u64,
+  pub gas_budget: Option<Uint128>,
+  pub cumulative_gas: Uint128, // Total gas across stored runs, what gas_budget is compared to
+  pub message_count: u64,
+  pub paused: bool,
+  pub renounced: bool,
+  pub created_at_height: u64,
+  pub lifetime_store_count: u64,
+}
+
+// Response data of StoreMessage and StoreFixedLength, including replays and duplicates
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StoreResult {
+  pub id: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -128,6 +866,8 @@ pub struct MessageResponse {
   pub content: String,
   pub length: u64,
   pub time: u64,
+  pub gas_used: Option<Uint128>,
+  pub archived: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -145,13 +885,32 @@ pub struct TestRunResponse {
   pub avg_gas: Uint128,
   pub chain: String,
   pub tx_count: u32, // Number of tx proofs
+  pub code_size: Option<u64>,
+  pub bytes: u64, // Reported payload bytes, 0 if unknown
+  pub min_gas: Option<Uint128>,
+  pub max_gas: Option<Uint128>,
+  pub median_gas: Option<Uint128>,
+  pub tx_proofs: Option<Vec<String>>, // Only filled by GetTestRun and GetTestRuns with include_proofs
+  pub tags: Vec<String>,
+  pub note: Option<String>,
+  pub seq: u64,
+  pub started_at: Option<u64>,
+  pub finished_at: Option<u64>,
+  pub duration_secs: Option<u64>,
+  pub msgs_per_sec: Option<u64>, // None when the duration is unknown or zero
+  pub node_version: Option<String>,
+  pub contract_version: Option<String>, // This contract's version at record time
+  pub coverage: Option<MessageCoverage>, // Set when the run listed its message_ids
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TestRunsResponse {
   pub runs: Vec<TestRunResponse>,
+  // Pass as start_after for the next page, None once the last page was returned
+  pub next_start_after: Option<String>,
 }
 
+/// Response to `GetGasSummary`, part of the stable query interface
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct GasSummary {
   pub msg_count: u64,
@@ -159,577 +918,7905 @@ pub struct GasSummary {
   pub avg_gas: Uint128,
   pub total_bytes: u64,
   pub gas_per_byte: Uint128,
+  pub avg_bytes_per_msg: u64,
+  pub gas_std_dev: Uint128, // Population std dev of per-run total gas
+  pub weighted_gas_per_byte: Uint128, // Byte-weighted mean of per-run avg_gas_per_byte
+  pub min_gas: Option<Uint128>, // Lowest reported min_gas across runs
+  pub max_gas: Option<Uint128>, // Highest reported max_gas across runs
+  pub min_msg_length: u64, // Shortest stored message, 0 with no messages
+  pub max_msg_length: u64, // Longest stored message, 0 with no messages
+  pub avg_msgs_per_sec: Option<u64>, // Over runs reporting a nonzero duration, None without any
+  pub runs_excluded_from_bytes: u64, // Runs adding no bytes, so gas_per_byte is partial when nonzero
+  // Some runs' bytes were estimated from their avg_gas, so gas_per_byte is approximate
+  pub estimated: bool,
+  // Spread of per-run avg_gas_per_byte, see gas_per_byte_spread; GetGasSummary only fills
+  // these for a `window` so it stays O(1), GetGasSummaryExact over every run
+  // None with fewer than two runs
+  pub stddev_gas_per_byte: Option<Uint128>,
+  pub p50: Option<Uint128>,
+  pub p90: Option<Uint128>,
+  pub p99: Option<Uint128>,
 }
 
-// Storage constants
-pub const STATE: Item<State> = Item::new("state");
-pub const MESSAGES: Map<&str, StoredMessage> = Map::new("msgs");
-pub const TEST_RUNS: Map<&str, TestRunStats> = Map::new("runs");
-pub const MAX_MESSAGE_SIZE: u64 = 10000; // Define a max msg size
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RelativeGasSummary {
+  pub summary: GasSummary,
+  pub baseline_gas_per_byte: Uint128,
+  pub ratio_bps: Uint128, // gas_per_byte * 10000 / baseline, 10000 means on the baseline
+}
 
-#[entry_point]
-pub fn instantiate(
-  deps: DepsMut,
-  _env: Env,
-  info: MessageInfo,
-  _msg: InstantiateMsg,
-) -> Result<Response, ContractError> {
-  let state = State {
-      owner: info.sender.clone(),
-      test_run_count: 0,
-      last_test_timestamp: None,
-  };
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainGasSummary {
+  pub chain_id: String,
+  pub run_count: u64,
+  pub msg_count: u64,
+  pub total_gas: Uint128,
+  pub gas_per_byte: Uint128,
+}
 
-  STATE.save(deps.storage, &state)?;
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainGasSummaryResponse {
+  pub chains: Vec<ChainGasSummary>,
+}
 
-  Ok(Response::new()
-      .add_attribute("method", "instantiate")
-      .add_attribute("owner", info.sender))
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainsResponse {
+  pub chains: Vec<String>,
 }
 
-#[entry_point]
-pub fn execute(
-  deps: DepsMut,
-  env: Env,
-  info: MessageInfo,
-  msg: ExecuteMsg,
-) -> Result<Response, ContractError> {
-  match msg {
-      ExecuteMsg::StoreMessage { content } => 
-          execute_store_message(deps, env, info, content),
-      ExecuteMsg::StoreFixedLength { content, length } => 
-          execute_store_fixed_length(deps, env, info, content, length),
-      ExecuteMsg::RecordTestRun { run_id, count, gas, avg_gas, chain, tx_proof } => 
-          execute_record_test_run(deps, env, info, run_id, count, gas, avg_gas, chain, tx_proof),
-      ExecuteMsg::ClearData {} => 
-          execute_clear_data(deps, env, info),
-  }
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GasCurvePoint {
+  pub length: u64,
+  pub avg_gas_per_byte: Uint128,
+  pub runs: u64, // Number of runs averaged into this point
 }
 
-/// Store msg with actual length
-pub fn execute_store_message(
-  deps: DepsMut,
-  env: Env,
-  _info: MessageInfo,
-  content: String,
-) -> Result<Response, ContractError> {
-  // Validate msg size
-  let length = content.len() as u64;
-  if length > MAX_MESSAGE_SIZE {
-      return Err(ContractError::MessageTooLarge { 
-          size: length, 
-          max: MAX_MESSAGE_SIZE 
-      });
-  }
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GasCurveResponse {
+  pub points: Vec<GasCurvePoint>,
+}
 
-  let id = format!("msg_{}", env.block.height);
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MessageBlockSpanResponse {
+  pub first_height: u64,
+  pub last_height: u64,
+  pub block_span: u64,
+}
 
-  let message = StoredMessage {
-      content,
-      length,
-      stored_at: env.block.time.seconds(),
-  };
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CodeSizePoint {
+  pub code_size: u64,
+  pub total_gas: Uint128,
+}
 
-  MESSAGES.save(deps.storage, &id, &message)?;
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GasVsCodeSizeResponse {
+  pub points: Vec<CodeSizePoint>,
+}
 
-  Ok(Response::new()
-      .add_attribute("action", "store_message")
-      .add_attribute("id", id)
-      .add_attribute("length", length.to_string()))
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+  Healthy,
+  // Cached totals in state disagree with stored data
+  Degraded,
+  // Contract is paused and not accepting writes
+  Frozen,
+  // Ownership renounced, recorded runs can no longer change
+  Archived,
 }
 
-// Store a message with a specific target length
-pub fn execute_store_fixed_length(
-  deps: DepsMut,
-  env: Env,
-  _info: MessageInfo,
-  content: String,
-  target_length: u64,
-) -> Result<Response, ContractError> {
-  // Validate target length
-  if target_length > MAX_MESSAGE_SIZE {
-      return Err(ContractError::MessageTooLarge { 
-          size: target_length, 
-          max: MAX_MESSAGE_SIZE 
-      });
-  }
-  
-  let id = format!("msg_{}_{}", env.block.height, target_length);
-  
-  // Adjust content to match target length
-  let adjusted_content = if content.len() as u64 > target_length {
-      // Truncate if too long
-      content.chars().take(target_length as usize).collect()
-  } else {
-      // Pad with spaces if too short
-      let padding = " ".repeat((target_length as usize).saturating_sub(content.len()));
-      format!("{}{}", content, padding)
-  };
-  
-  let actual_length = adjusted_content.len() as u64;
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HealthResponse {
+  pub status: HealthStatus,
+  pub reason: Option<String>,
+}
 
-  // Verify adjustment worked correctly
-  if actual_length != target_length {
-      return Err(ContractError::InvalidMessageLength { 
-          length: actual_length, 
-          expected: target_length 
-      });
-  }
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LivenessResponse {
+  pub ok: bool,
+  pub paused: bool,
+  pub message_count: u64,
+}
 
-  let message = StoredMessage {
-      content: adjusted_content,
-      length: actual_length,
-      stored_at: env.block.time.seconds(),
-  };
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RunTimeBoundsResponse {
+  pub oldest: Option<u64>,
+  pub newest: Option<u64>,
+  pub run_count: u64,
+}
 
-  MESSAGES.save(deps.storage, &id, &message)?;
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GasPoint {
+  pub time: u64,
+  pub gas_per_byte: Uint128,
+  pub chain: String,
+}
 
-  Ok(Response::new()
-      .add_attribute("action", "store_fixed_length")
-      .add_attribute("id", id)
-      .add_attribute("length", actual_length.to_string()))
+// Position in the run time index, the last run a GetGasSeries page read
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SeriesCursor {
+  pub time: u64,
+  pub chain: String,
+  pub run_id: String,
 }
 
-// Record test run statistics
-pub fn execute_record_test_run(
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GasSeriesResponse {
+  pub points: Vec<GasPoint>,
+  pub next: Option<SeriesCursor>, // None once the range is exhausted
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LengthBucket {
+  pub start: u64, // Inclusive
+  pub end: u64,   // Exclusive
+  pub count: u64,
+  pub total_bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LengthHistogramResponse {
+  pub buckets: Vec<LengthBucket>, // Ascending, empty bins left out
+  pub scanned: u64, // Messages read, fewer than the message count when max_scan cut the scan short
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CounterResponse {
+  pub name: String,
+  pub value: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VersionResponse {
+  pub contract: String,
+  pub version: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RunDecompositionResponse {
+  pub run_id: String,
+  pub overhead_gas: Uint128, // Fixed per-tx cost (GetGasModel intercept * message count)
+  pub payload_gas: Uint128,  // Remainder of total_gas attributed to message bytes
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClearHistoryResponse {
+  pub events: Vec<ClearEvent>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DashboardResponse {
+  pub config: ConfigResponse,
+  pub summary: GasSummary,
+  pub runs: Vec<TestRunResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RunSamplesResponse {
+  pub samples: Vec<GasSample>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConcatMessagesResponse {
+  pub content: String,
+  pub count: u64, // Messages joined
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportRunsCsvResponse {
+  pub csv: String,
+  pub next_start_after: Option<String>, // Same cursor as GetTestRuns, None on the last page
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidateMessageResponse {
+  pub valid: bool,
+  pub length: u64,
+  pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecordersResponse {
+  pub recorders: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StorageStatsResponse {
+  pub message_count: u64,
+  pub test_run_count: u64,
+  pub estimated_message_bytes: u64, // Sum of stored message lengths
+}
+
+// Least-squares fit of gas = intercept + slope * bytes
+// slope_scaled and r_squared_scaled hold the value * GAS_MODEL_SCALE since floats aren't available
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearFit {
+  pub intercept: i128,
+  pub slope_scaled: i128,
+  pub r_squared_scaled: i128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsOwnerResponse {
+  pub is_owner: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GasEstimateResponse {
+  pub estimated_gas: Uint128,
+  pub model: String, // "linear_fit" over samples or "avg_gas_per_byte" fallback
+  pub runs_used: u64,
+}
+
+// Gas model fitted over recorded samples
+// slope and r_squared are fixed point, divide by `scale` for the real value
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GasModelResponse {
+  pub intercept: Int128,
+  pub slope: Int128,
+  pub r_squared: Uint128,
+  pub scale: Uint128,
+  pub sample_count: u64,
+}
+
+// One metric in both runs, change_bps is the delta relative to base in basis points
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MetricDelta {
+  pub base: Uint128,
+  pub against: Uint128,
+  pub delta: Int128,
+  pub change_bps: Int128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RunComparisonResponse {
+  pub base: String,
+  pub against: String,
+  pub avg_gas_per_byte: MetricDelta,
+  pub gas_per_message: MetricDelta,
+  pub message_count: MetricDelta,
+  pub threshold_bps: u64,
+  // avg_gas_per_byte or gas_per_message grew by more than threshold_bps
+  pub regression: bool,
+}
+
+// Version info for cw2
+pub const CONTRACT_NAME: &str = "crates.io:cw-gas-test";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Storage constants
+pub const STATE: Item<State> = Item::new("state");
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const MESSAGES: Map<&str, StoredMessage> = Map::new("msgs");
+// Runs keyed by (chain_id, run_id), so testnets can reuse run ids
+pub const TEST_RUNS: Map<(&str, &str), TestRunStats> = Map::new("chain_runs");
+// run_id -> chains it was recorded on, for run_id lookups and run_id ordered listings
+pub const RUN_CHAINS: Map<&str, Vec<String>> = Map::new("run_chains");
+// chain_id -> id of its newest run, ties going to the greater run_id
+pub const LATEST_RUNS: Map<&str, String> = Map::new("latest_runs");
+// (timestamp, chain_id, run_id) of every run, for chronological listings
+pub const RUN_TIMES: Map<(u64, &str, &str), Empty> = Map::new("run_times");
+// seq -> (chain_id, run_id) of the run it was assigned to
+pub const RUN_SEQS: Map<u64, (String, String)> = Map::new("run_seqs");
+// Runs keyed by run_id alone, only read by migrate
+pub const LEGACY_TEST_RUNS: Map<&str, TestRunStats> = Map::new("runs");
+// Idempotency key -> id of the message stored under it, dropped with the message
+pub const IDEMPOTENCY_KEYS: Map<&str, String> = Map::new("idem");
+// Message id -> idempotency key that produced it, so removals can drop the key
+pub const IDEMPOTENCY_IDS: Map<&str, String> = Map::new("idem_ids");
+// sha256 of content -> message id, only written by StoreMessage with dedup
+pub const CONTENT_HASHES: Map<&[u8], String> = Map::new("content_hash");
+pub const CUSTOM_COUNTERS: Map<&str, u64> = Map::new("counters");
+// Most recent clear operations, oldest first
+pub const CLEAR_HISTORY: Deque<ClearEvent> = Deque::new("clear_history");
+// Running sums behind GetGasSummary, see load_aggregates
+pub const AGGREGATES: Item<Aggregates> = Item::new("aggregates");
+// Counting indexes, the first and last keys give the summary's extrema
+pub const MESSAGE_LENGTHS: Map<u64, u64> = Map::new("msg_lengths");
+pub const RUN_MIN_GAS: Map<u128, u64> = Map::new("run_min_gas");
+pub const RUN_MAX_GAS: Map<u128, u64> = Map::new("run_max_gas");
+pub const MAX_CLEAR_HISTORY: u32 = 20;
+pub const MAX_MESSAGE_SIZE: u64 = 10000; // Default max msg size
+pub const DEFAULT_MAX_LIST_LIMIT: u32 = 30;
+pub const DEFAULT_MAX_RUNS_LIMIT: u32 = 20;
+// Hard ceilings for UpdateConfig
+pub const MAX_MESSAGE_SIZE_CEILING: u64 = 1_000_000;
+pub const MAX_PAGE_LIMIT_CEILING: u32 = 100;
+pub const MAX_READS: u32 = 1000; // Max repeated loads in StoreAndRead
+pub const MAX_READ_IDS: usize = 500; // Max ids per ReadMessages call
+pub const MAX_ITERATE: u32 = 5000; // Max entries visited per IterateMessages call
+pub const MAX_DELETE_RANGE: u32 = 1000; // Max messages removed per DeleteRange call
+pub const MAX_RECORD_BATCH: usize = 50; // Max runs per RecordTestRunBatch call
+pub const DEFAULT_REGRESSION_THRESHOLD_BPS: u64 = 500; // 5%
+pub const DEFAULT_AVG_GAS_TOLERANCE_BPS: u64 = 1000; // 10% drift of avg_gas from gas / bytes
+pub const MAX_AVG_GAS_TOLERANCE_BPS: u64 = 10_000; // Widest avg_gas tolerance UpdateConfig accepts
+pub const GAS_MODEL_SCALE: i128 = 1_000_000; // Fixed-point scale for fitted slopes
+pub const MAX_TX_PROOFS: usize = 500; // Max proofs stored per test run
+pub const MAX_TX_PROOF_LENGTH: usize = 128; // Max length of a single proof
+pub const DEFAULT_MAX_PROOF_BYTES: u64 = 32 * 1024; // Default cap on a run's combined proof length
+pub const MAX_GAS_SAMPLES: usize = 200; // Max samples stored per test run
+pub const MAX_RUN_TAGS: usize = 10; // Max tags per test run
+pub const MAX_RUN_TAG_LENGTH: usize = 32; // Max bytes in a single tag
+pub const MAX_RUN_NOTE_LENGTH: usize = 256; // Max bytes in a run note
+pub const MAX_NODE_VERSION_LENGTH: usize = 64; // Max bytes in a run's node_version
+pub const MAX_RUN_MESSAGE_IDS: usize = 500; // Max message_ids referenced by one run
+pub const MAX_RUN_ID_LENGTH: usize = 64; // Max length of a newly recorded run_id
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 128; // Max length of an idempotency key
+pub const DEFAULT_SERIES_LIMIT: u32 = 100; // Default GetGasSeries page size
+pub const MAX_SERIES_LIMIT: u32 = 500; // Max GetGasSeries page size
+pub const MAX_SERIES_SCAN: usize = 2000; // Max time index entries visited per GetGasSeries call
+pub const MAX_PROOF_PAGE_LIMIT: u32 = 5; // Max GetTestRuns limit with include_proofs
+pub const DEFAULT_MIGRATE_LIMIT: u32 = 500; // Default legacy runs moved per migrate call
+
+#[entry_point]
+pub fn instantiate(
   deps: DepsMut,
   env: Env,
   info: MessageInfo,
-  run_id: String,
-  count: u64,
-  gas: Uint128,
-  avg_gas: Uint128,
-  chain: String,
-  tx_proof: Option<String>,
+  msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-  // Validate run_id format
-  if run_id.trim().is_empty() {
-      return Err(ContractError::InvalidRunId("Run ID cannot be empty".into()));
-  }
+  let owner = match msg.owner {
+      Some(owner) => deps.api.addr_validate(&owner)?,
+      None => info.sender,
+  };
 
-  // Validate chain id format
-  if chain.trim().is_empty() {
-      return Err(ContractError::InvalidChainId("Chain ID cannot be empty".into()));
+  if msg.max_messages == Some(0) {
+      return Err(ContractError::InvalidConfig("max_messages must be at least 1".into()));
   }
 
-  // Validate gas value
-  if gas.is_zero() && count > 0 {
-      return Err(ContractError::InvalidGasValue("Gas cannot be zero for non-empty test runs".into()));
-  }
-  
-  // Only owner can record test runs
-  let state = STATE.load(deps.storage)?;
-  if info.sender != state.owner {
-      return Err(ContractError::Unauthorized {});
-  }
-  
-  let test_run = TestRunStats {
-      timestamp: env.block.time.seconds(),
-      message_count: count,
-      total_gas: gas,
-      avg_gas_per_byte: avg_gas,
-      chain_id: chain,
-      tx_proof: tx_proof.clone(),
+  let state = State {
+      owner: owner.clone(),
+      test_run_count: 0,
+      last_test_timestamp: None,
+      message_count: 0,
+      paused: false,
+      recorders: vec![],
+      renounced: false,
+      created_at_height: env.block.height,
+      lifetime_store_count: 0,
+      last_run_seq: 0,
   };
-  
-  TEST_RUNS.save(deps.storage, &run_id, &test_run)?;
-  
-  // Update state
-  let mut updated_state = state;
-  updated_state.test_run_count += 1;
-  updated_state.last_test_timestamp = Some(env.block.time.seconds());
-  STATE.save(deps.storage, &updated_state)?;
-  
-  let tx_count = tx_proof.as_ref().map_or(0, |hashes| {
-      hashes.split(',').count() as u32
-  });
-  
+
+  let config = Config {
+      max_messages: msg.max_messages,
+      ..Config::default()
+  };
+
+  STATE.save(deps.storage, &state)?;
+  CONFIG.save(deps.storage, &config)?;
+  AGGREGATES.save(deps.storage, &Aggregates::default())?;
+  set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
   Ok(Response::new()
-      .add_attribute("action", "record_test_run")
-      .add_attribute("run_id", run_id)
-      .add_attribute("count", count.to_string())
-      .add_attribute("gas", gas.to_string())
-      .add_attribute("tx_count", tx_count.to_string()))
+      .add_attribute("method", "instantiate")
+      .add_attribute("owner", owner))
 }
 
-// Clear all stored data (admin only)
-pub fn execute_clear_data(
-  deps: DepsMut,
-  env: Env,
-  info: MessageInfo,
-) -> Result<Response, ContractError> {
-  let state = STATE.load(deps.storage)?;
+// Move runs stored under their bare run_id to the (chain_id, run_id) keys
+// The aggregates and run count already cover them, only the keys change
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+  ensure_migratable(deps.storage)?;
   
-  // Only owner can clear data
-  if info.sender != state.owner {
-      return Err(ContractError::Unauthorized {});
+  // Legacy runs move over in batches of `limit`, migrate again with the same code until
+  // `legacy_remaining` is 0. The rebuild below only runs on that last call and still reads
+  // every run in one transaction, so it is bounded by the block gas limit
+  let limit = msg.limit.unwrap_or(DEFAULT_MIGRATE_LIMIT) as usize;
+  if limit == 0 {
+      return Err(ContractError::InvalidMigration("limit must be at least 1".to_string()));
+  }
+  let legacy = LEGACY_TEST_RUNS
+      .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .take(limit)
+      .collect::<StdResult<Vec<_>>>()?;
+  
+  for (run_id, mut run) in legacy.iter().cloned() {
+      run.chain_id = normalize_chain_id(&run.chain_id);
+      TEST_RUNS.save(deps.storage, (&run.chain_id, &run_id), &run)?;
+      index_run_chain(deps.storage, &run_id, &run.chain_id, true)?;
+      LEGACY_TEST_RUNS.remove(deps.storage, &run_id);
   }
   
-  // Delete all messages (range_raw for efficiency)
-  let keys_to_remove: Vec<String> = MESSAGES
+  let response = Response::new()
+      .add_attribute("method", "migrate")
+      .add_attribute("runs_migrated", legacy.len().to_string());
+  let legacy_remaining = LEGACY_TEST_RUNS
       .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-      .collect::<Result<Vec<_>, _>>()?;
+      .take(limit)
+      .count();
+  if legacy_remaining > 0 {
+      return Ok(response.add_attribute("legacy_remaining", legacy_remaining.to_string()));
+  }
   
-  for key in keys_to_remove {
-      MESSAGES.remove(deps.storage, &key);
+  // Rebuild the latest run pointers and time index, covering runs recorded before they were kept
+  let runs = TEST_RUNS
+      .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .map(|item| item.map(|(key, run)| (key, run.timestamp)))
+      .collect::<StdResult<Vec<_>>>()?;
+  let mut chains = BTreeSet::new();
+  for ((chain, run_id), timestamp) in runs {
+      RUN_TIMES.save(deps.storage, (timestamp, &chain, &run_id), &Empty {})?;
+      chains.insert(chain);
+  }
+  for chain in chains {
+      refresh_latest_run(deps.storage, &chain)?;
   }
   
-  // Delete all test runs
-  let run_keys_to_remove: Vec<String> = TEST_RUNS
+  // Number runs recorded before sequence numbers, oldest first
+  let unnumbered = RUN_TIMES
       .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-      .collect::<Result<Vec<_>, _>>()?;
-  
-  for key in run_keys_to_remove {
-      TEST_RUNS.remove(deps.storage, &key);
+      .collect::<StdResult<Vec<_>>>()?;
+  let mut state = STATE.load(deps.storage)?;
+  for (_, chain, run_id) in unnumbered {
+      let mut run = TEST_RUNS.load(deps.storage, (&chain, &run_id))?;
+      if run.seq == 0 {
+          run.seq = assign_run_seq(deps.storage, &mut state, &chain, &run_id)?;
+          TEST_RUNS.save(deps.storage, (&chain, &run_id), &run)?;
+      }
   }
+  STATE.save(deps.storage, &state)?;
   
-  // Update state but keep configuration
-  let updated_state = State {
-      owner: state.owner,
-      test_run_count: 0,
-      last_test_timestamp: Some(env.block.time.seconds()),
-  };
+  // Recount the byte totals the aggregates didn't track before
+  if let Some(mut aggregates) = AGGREGATES.may_load(deps.storage)? {
+      let overflow = |field: &str| StdError::generic_err(format!("gas summary overflow: {} is out of range", field));
+      aggregates.runs_without_bytes = 0;
+      aggregates.runs_estimated_bytes = 0;
+      for item in TEST_RUNS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+          let (_, run) = item?;
+          match (run_bytes(&run), run.total_bytes) {
+              (0, _) => {
+                  aggregates.runs_without_bytes = aggregates.runs_without_bytes.checked_add(1).ok_or_else(|| overflow("runs_without_bytes"))?;
+              }
+              (_, 0) => {
+                  aggregates.runs_estimated_bytes = aggregates.runs_estimated_bytes.checked_add(1).ok_or_else(|| overflow("runs_estimated_bytes"))?;
+              }
+              _ => {}
+          }
+      }
+      AGGREGATES.save(deps.storage, &aggregates)?;
+  }
+  set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
   
-  STATE.save(deps.storage, &updated_state)?;
+  Ok(response.add_attribute("legacy_remaining", "0"))
+}
+
+// Only this contract may be migrated, and never to an older version
+fn ensure_migratable(storage: &dyn Storage) -> Result<(), ContractError> {
+  let stored = get_contract_version(storage)
+      .map_err(|_| ContractError::InvalidMigration("no cw2 contract version stored".to_string()))?;
+  if stored.contract != CONTRACT_NAME {
+      return Err(ContractError::InvalidMigration(format!(
+          "cannot migrate {} to {}", stored.contract, CONTRACT_NAME
+      )));
+  }
   
-  Ok(Response::new()
-      .add_attribute("action", "clear_data")
-      .add_attribute("time", env.block.time.seconds().to_string()))
+  // Compares the numeric major.minor.patch, pre-release suffixes are ignored
+  let parse = |version: &str| -> Result<Vec<u64>, ContractError> {
+      version
+          .split(['-', '+'])
+          .next()
+          .unwrap_or_default()
+          .split('.')
+          .map(|part| part.parse::<u64>())
+          .collect::<Result<Vec<_>, _>>()
+          .map_err(|_| ContractError::InvalidMigration(format!("unparseable version {}", version)))
+  };
+  if parse(&stored.version)? > parse(CONTRACT_VERSION)? {
+      return Err(ContractError::InvalidMigration(format!(
+          "cannot downgrade from {} to {}", stored.version, CONTRACT_VERSION
+      )));
+  }
+  Ok(())
 }
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn execute(
+  deps: DepsMut,
+  env: Env,
+  info: MessageInfo,
+  msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+  // Everything but the pause switch itself is blocked while paused
+  if !matches!(msg, ExecuteMsg::SetPaused { .. }) {
+      ensure_not_paused(deps.storage)?;
+  }
+
   match msg {
-      QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
-      QueryMsg::GetMessage { id } => to_json_binary(&query_message(deps, id)?),
-      QueryMsg::ListMessages { start_after, limit } => to_json_binary(&query_list_messages(deps, start_after, limit)?),
-      QueryMsg::GetTestRuns { start_after, limit } => to_json_binary(&query_test_runs(deps, start_after, limit)?),
-      QueryMsg::GetGasSummary {} => to_json_binary(&query_gas_summary(deps)?),
+      ExecuteMsg::StoreMessage { content, idempotency_key, gas_used, dedup, compress } => 
+          execute_store_message(deps, env, info, content, idempotency_key, gas_used, dedup.unwrap_or(false), compress.unwrap_or(false)),
+      ExecuteMsg::StoreFixedLength { content, length, idempotency_key } => 
+          execute_store_fixed_length(deps, env, info, content, length, idempotency_key),
+      ExecuteMsg::RecordTestRun { run_id, count, gas, avg_gas, chain, tx_proofs, tx_hashes, byte_length, overwrite, code_size_bytes, bytes, min_gas, max_gas, median_gas, samples, gas_price, fee_denom, tags, note, started_at, finished_at, node_version, message_ids } => 
+          execute_record_test_run(deps, env, info, run_id, count, gas, avg_gas, chain, tx_hashes.or(tx_proofs), byte_length, overwrite, code_size_bytes, bytes, min_gas, max_gas, median_gas, samples, gas_price, fee_denom, tags, note, (started_at, finished_at), node_version, message_ids),
+      ExecuteMsg::RecordTestRunBatch { runs } => execute_record_test_run_batch(deps, env, info, runs),
+      ExecuteMsg::UpdateTestRun { run_id, count, gas, avg_gas, chain, tx_proofs, tx_hashes, byte_length, code_size_bytes, bytes, min_gas, max_gas, median_gas, samples, gas_price, fee_denom } => 
+          execute_update_test_run(deps, env, info, run_id, count, gas, avg_gas, chain, tx_hashes.or(tx_proofs), byte_length, code_size_bytes, bytes, min_gas, max_gas, median_gas, samples, gas_price, fee_denom),
+      ExecuteMsg::ClearData { limit } => 
+          execute_clear_data(deps, env, info, limit),
+      ExecuteMsg::UpdateConfig { max_message_size, max_list_limit, max_runs_limit, max_messages, eviction, hex_tx_proofs, strict_validation, regression_threshold_bps, min_gas_per_byte, max_gas_per_byte, avg_gas_tolerance_bps, open_recording, max_proof_bytes, gas_budget } => 
+          execute_update_config(deps, env, info, max_message_size, max_list_limit, max_runs_limit, max_messages, eviction, hex_tx_proofs, strict_validation, regression_threshold_bps, min_gas_per_byte, max_gas_per_byte, avg_gas_tolerance_bps, open_recording, max_proof_bytes, gas_budget),
+      ExecuteMsg::StoreAndRead { content, reads } => 
+          execute_store_and_read(deps, env, info, content, reads),
+      ExecuteMsg::ReadMessages { ids } => 
+          execute_read_messages(deps, env, info, ids),
+      ExecuteMsg::IterateMessages { limit, ascending } => 
+          execute_iterate_messages(deps, env, info, limit, ascending),
+      ExecuteMsg::IncrementCounter { name } => 
+          execute_increment_counter(deps, env, info, name),
+      ExecuteMsg::ResetCounter { name } => 
+          execute_reset_counter(deps, env, info, name),
+      ExecuteMsg::ResetCounters {} => 
+          execute_reset_counters(deps, env, info),
+      ExecuteMsg::DeleteRange { start_after, limit } => 
+          execute_delete_range(deps, env, info, start_after, limit),
+      ExecuteMsg::SetPaused { paused } => 
+          execute_set_paused(deps, env, info, paused),
+      ExecuteMsg::UpdateRecorders { add, remove } => 
+          execute_update_recorders(deps, env, info, add, remove),
+      ExecuteMsg::RenounceOwnership { confirm } => 
+          execute_renounce_ownership(deps, env, info, confirm),
+      ExecuteMsg::ClearMessages { prefix, limit } => 
+          execute_clear_messages(deps, env, info, prefix, limit),
+      ExecuteMsg::ClearTestRuns { prefix, limit } => 
+          execute_clear_test_runs(deps, env, info, prefix, limit),
+      ExecuteMsg::DeleteTestRun { run_id, chain } => 
+          execute_delete_test_run(deps, env, info, run_id, chain),
+      ExecuteMsg::UpdateMessage { id, content } => 
+          execute_update_message(deps, env, info, id, content),
+      ExecuteMsg::AppendToMessage { id, suffix } => 
+          execute_append_to_message(deps, env, info, id, suffix),
+      ExecuteMsg::ArchiveMessage { id, archived } => 
+          execute_archive_message(deps, env, info, id, archived),
+      ExecuteMsg::AppendTxProofs { run_id, proofs, chain } => 
+          execute_append_tx_proofs(deps, env, info, run_id, proofs, chain),
   }
 }
 
-// Query contract configuration
-fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
-  let state = STATE.load(deps.storage)?;
-  
-  Ok(ConfigResponse {
-      owner: state.owner.to_string(),
-      test_count: state.test_run_count,
-      last_test: state.last_test_timestamp,
-  })
+// Load config, falling back to defaults for contracts instantiated before it existed
+pub fn load_config(storage: &dyn Storage) -> StdResult<Config> {
+  Ok(CONFIG.may_load(storage)?.unwrap_or_default())
 }
 
-// Query msg by id
-fn query_message(deps: Deps, id: String) -> StdResult<MessageResponse> {
-  let message = MESSAGES.load(deps.storage, &id)?;
-  
-  Ok(MessageResponse {
-      id,
-      content: message.content,
-      length: message.length,
-      time: message.stored_at,
-  })
+// Shared gate for state-mutating actions; the dispatcher runs it for every
+// variant except SetPaused so new execute messages are covered by default
+fn ensure_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+  if STATE.load(storage)?.paused {
+      return Err(ContractError::Paused {});
+  }
+  Ok(())
 }
 
-/// List msgs paginated
-fn query_list_messages(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<ListMessagesResponse> {
-  // Default limit is 10, max allowed is 30
-  let limit = limit.unwrap_or(10).min(30) as usize;
-  
-  // Convert start_after to Bound
-  let start = start_after.as_deref().map(Bound::exclusive);
-
-  let messages: StdResult<Vec<_>> = MESSAGES
-      .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
-      .take(limit)
-      .map(|item| {
-          let (id, message) = item?;
-          Ok(MessageResponse {
-              id: id.to_string(),
-              content: message.content,
-              length: message.length,
-              time: message.stored_at,
-          })
-      })
-      .collect();
+// Save a message, enforcing max_messages and keeping message_count in sync
+// Overwriting an existing id doesn't change the count
+// Returns the id evicted to make room, if any
+fn save_message(storage: &mut dyn Storage, id: &str, message: &StoredMessage) -> Result<Option<String>, ContractError> {
+  let mut evicted = None;
+  let mut state = STATE.load(storage)?;
+  state.lifetime_store_count += 1;
   
-  let msgs = messages?;
+  if let Some(previous) = MESSAGES.may_load(storage, id)? {
+      track_message(storage, previous.length, false)?;
+      forget_idempotency_key(storage, id)?;
+  } else {
+      let config = load_config(storage)?;
+      if let Some(max) = config.max_messages {
+          if state.message_count >= max {
+              // Evict the lowest id (oldest height-based key) when enabled
+              let oldest = if config.eviction {
+                  MESSAGES
+                      .keys(storage, None, None, cosmwasm_std::Order::Ascending)
+                      .next()
+                      .transpose()?
+              } else {
+                  None
+              };
+              
+              match oldest {
+                  Some(oldest) => {
+                      let oldest_length = MESSAGES.load(storage, &oldest)?.length;
+                      track_message(storage, oldest_length, false)?;
+                      MESSAGES.remove(storage, &oldest);
+                      forget_idempotency_key(storage, &oldest)?;
+                      evicted = Some(oldest);
+                  }
+                  None => {
+                      return Err(ContractError::StorageFull { 
+                          current: state.message_count, 
+                          max 
+                      });
+                  }
+              }
+          }
+      }
+      
+      if evicted.is_none() {
+          state.message_count += 1;
+      }
+  }
+  STATE.save(storage, &state)?;
   
-  Ok(ListMessagesResponse {
-      msgs: msgs.clone(),
-      count: msgs.len() as u64,
-  })
+  track_message(storage, message.length, true)?;
+  MESSAGES.save(storage, id, message)?;
+  Ok(evicted)
 }
 
-/// Query prev runs paginated
-fn query_test_runs(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<TestRunsResponse> {
-  // Default limit is 5, max allowed is 20
-  let limit = limit.unwrap_or(5).min(20) as usize;
-  
-  // Convert start_after to Bound
-  let start = start_after.as_deref().map(Bound::exclusive);
+// Remove messages by id, keeping message_count in sync
+// Returns the total length of the removed messages
+fn remove_messages(storage: &mut dyn Storage, ids: &[String]) -> StdResult<u64> {
+  let mut state = STATE.load(storage)?;
+  let mut bytes_freed = 0u64;
+  for id in ids {
+      if let Some(message) = MESSAGES.may_load(storage, id)? {
+          track_message(storage, message.length, false)?;
+          MESSAGES.remove(storage, id);
+          forget_idempotency_key(storage, id)?;
+          state.message_count = state.message_count.saturating_sub(1);
+          bytes_freed += message.length;
+      }
+  }
+  STATE.save(storage, &state)?;
+  Ok(bytes_freed)
+}
 
-  let runs: StdResult<Vec<_>> = TEST_RUNS
-      .range(deps.storage, start, None, cosmwasm_std::Order::Descending)
-      .take(limit)
-      .map(|item| {
-          let (id, run) = item?;
-          
-          // Count tx proofs
-          let tx_count = run.tx_proof.as_ref().map_or(0, |proof| {
-              proof.split(',').count() as u32
-          });
-          
-          Ok(TestRunResponse {
-              id,
-              time: run.timestamp,
-              count: run.message_count,
-              gas: run.total_gas,
-              avg_gas: run.avg_gas_per_byte,
-              chain: run.chain_id,
-              tx_count,
-          })
-      })
-      .collect();
-  
-  Ok(TestRunsResponse { runs: runs? })
+// Append to the clear history, dropping the oldest beyond MAX_CLEAR_HISTORY
+fn record_clear_event(storage: &mut dyn Storage, event: &ClearEvent) -> StdResult<()> {
+  CLEAR_HISTORY.push_back(storage, event)?;
+  while CLEAR_HISTORY.len(storage)? > MAX_CLEAR_HISTORY {
+      CLEAR_HISTORY.pop_front(storage)?;
+  }
+  Ok(())
 }
 
-/// Query gas usage metrics
-fn query_gas_summary(deps: Deps) -> StdResult<GasSummary> {
-  // Compute summary statistics from stored test runs
-  let runs: StdResult<Vec<TestRunStats>> = TEST_RUNS
-      .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-      .map(|item| item.map(|(_, run)| run))
-      .collect();
-  
-  let runs = runs?;
-  let run_count = runs.len() as u64;
-  
-  if run_count == 0 {
-      return Ok(GasSummary {
-          msg_count: 0,
-          total_gas: Uint128::zero(),
-          avg_gas: Uint128::zero(),
-          total_bytes: 0,
-          gas_per_byte: Uint128::zero(),
-      });
+// Remove test runs by (chain_id, run_id), keeping test_run_count and the aggregates in sync
+fn remove_test_runs(storage: &mut dyn Storage, keys: &[(String, String)]) -> StdResult<()> {
+  let mut state = STATE.load(storage)?;
+  for (chain, run_id) in keys {
+      if let Some(run) = TEST_RUNS.may_load(storage, (chain, run_id))? {
+          track_run(storage, &run, false)?;
+          TEST_RUNS.remove(storage, (chain, run_id));
+          RUN_TIMES.remove(storage, (run.timestamp, chain, run_id));
+          RUN_SEQS.remove(storage, run.seq);
+          index_run_chain(storage, run_id, chain, false)?;
+          index_latest_run(storage, chain, run_id, None)?;
+          state.test_run_count = state.test_run_count.saturating_sub(1);
+      }
   }
-  
-  // Calculate aggregates
-  let mut total_messages = 0u64;
-  let mut total_gas = Uint128::zero();
-  let mut total_bytes = 0u64;
-  
-  for run in runs {
-      total_messages += run.message_count;
-      total_gas += run.total_gas;
-      
-      // Estimate total bytes based on average gas per byte
-      if !run.avg_gas_per_byte.is_zero() {
-          let run_bytes = run.total_gas.u128() as u64 / run.avg_gas_per_byte.u128() as u64;
-          total_bytes += run_bytes;
+  STATE.save(storage, &state)
+}
+
+// Add or remove a chain from a run id's entry in RUN_CHAINS
+fn index_run_chain(storage: &mut dyn Storage, run_id: &str, chain: &str, add: bool) -> StdResult<()> {
+  let mut chains = RUN_CHAINS.may_load(storage, run_id)?.unwrap_or_default();
+  match (add, chains.binary_search_by(|c| c.as_str().cmp(chain))) {
+      (true, Err(index)) => chains.insert(index, chain.to_string()),
+      (false, Ok(index)) => {
+          chains.remove(index);
       }
+      _ => return Ok(()),
   }
   
-  // Calculate averages (safely handle division by zero)
-  let avg_gas = if total_messages > 0 {
-      Uint128::new(total_gas.u128() / total_messages as u128)
+  if chains.is_empty() {
+      RUN_CHAINS.remove(storage, run_id);
+      Ok(())
   } else {
-      Uint128::zero()
+      RUN_CHAINS.save(storage, run_id, &chains)
+  }
+}
+
+// Keep LATEST_RUNS current after a run was saved with a new timestamp, or removed
+// when timestamp is None; only a change to the chain's latest run rescans the chain
+fn index_latest_run(storage: &mut dyn Storage, chain: &str, run_id: &str, timestamp: Option<u64>) -> StdResult<()> {
+  let newer = match (LATEST_RUNS.may_load(storage, chain)?, timestamp) {
+      (Some(latest), _) if latest == run_id => return refresh_latest_run(storage, chain),
+      (Some(latest), Some(timestamp)) => {
+          let latest_timestamp = TEST_RUNS.load(storage, (chain, &latest))?.timestamp;
+          (timestamp, run_id) > (latest_timestamp, latest.as_str())
+      }
+      (None, timestamp) => timestamp.is_some(),
+      (Some(_), None) => false,
   };
   
-  let gas_per_byte = if total_bytes > 0 {
-      Uint128::new(total_gas.u128() / total_bytes as u128)
-  } else {
-      Uint128::zero()
+  if newer {
+      LATEST_RUNS.save(storage, chain, &run_id.to_string())?;
+  }
+  Ok(())
+}
+
+// Point LATEST_RUNS at the newest run on a chain by scanning its runs
+fn refresh_latest_run(storage: &mut dyn Storage, chain: &str) -> StdResult<()> {
+  let mut latest: Option<(u64, String)> = None;
+  for item in TEST_RUNS.prefix(chain).range(storage, None, None, cosmwasm_std::Order::Ascending) {
+      let (run_id, run) = item?;
+      // Ascending ids, so >= lets the greater id win a tie
+      if latest.as_ref().map_or(true, |(timestamp, _)| run.timestamp >= *timestamp) {
+          latest = Some((run.timestamp, run_id));
+      }
+  }
+  
+  match latest {
+      Some((_, run_id)) => LATEST_RUNS.save(storage, chain, &run_id),
+      None => {
+          LATEST_RUNS.remove(storage, chain);
+          Ok(())
+      }
+  }
+}
+
+// Find a run by id, returning its chain too
+// Without a chain the id must only be recorded on one chain
+fn find_run(storage: &dyn Storage, run_id: &str, chain: Option<&str>) -> StdResult<Option<(String, TestRunStats)>> {
+  let chain = match chain {
+      Some(chain) => normalize_chain_id(chain),
+      None => {
+          let chains = RUN_CHAINS.may_load(storage, run_id)?.unwrap_or_default();
+          match chains.as_slice() {
+              [] => return Ok(None),
+              [chain] => chain.clone(),
+              _ => {
+                  return Err(StdError::generic_err(format!(
+                      "test run {} exists on chains {}, specify a chain", run_id, chains.join(", ")
+                  )))
+              }
+          }
+      }
   };
   
-  Ok(GasSummary {
-      msg_count: total_messages,
-      total_gas,
-      avg_gas,
-      total_bytes,
-      gas_per_byte,
-  })
+  Ok(TEST_RUNS.may_load(storage, (&chain, run_id))?.map(|run| (chain, run)))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary};
+// Load the running aggregates, contracts that stored data before they existed
+// get them and the extrema indexes rebuilt from a full scan on the first write
+fn load_aggregates(storage: &mut dyn Storage) -> StdResult<Aggregates> {
+  if let Some(aggregates) = AGGREGATES.may_load(storage)? {
+      return Ok(aggregates);
+  }
+  
+  let runs = TEST_RUNS
+      .range(storage, None, None, cosmwasm_std::Order::Ascending)
+      .map(|item| item.map(|(_, run)| run))
+      .collect::<StdResult<Vec<_>>>()?;
+  let lengths = MESSAGES
+      .range(storage, None, None, cosmwasm_std::Order::Ascending)
+      .map(|item| item.map(|(_, message)| message.length))
+      .collect::<StdResult<Vec<_>>>()?;
+  
+  let mut aggregates = Aggregates::default();
+  for run in &runs {
+      aggregates.add(run)?;
+      index_gas_extrema(storage, run, true)?;
+  }
+  for length in lengths {
+      adjust_index(&MESSAGE_LENGTHS, storage, length, true)?;
+  }
+  
+  AGGREGATES.save(storage, &aggregates)?;
+  Ok(aggregates)
+}
+
+// Add or remove a run from the aggregates, call before the run itself is written or removed
+fn track_run(storage: &mut dyn Storage, run: &TestRunStats, add: bool) -> StdResult<Aggregates> {
+  let mut aggregates = load_aggregates(storage)?;
+  if add {
+      aggregates.add(run)?;
+  } else {
+      aggregates.remove(run);
+  }
+  AGGREGATES.save(storage, &aggregates)?;
+  index_gas_extrema(storage, run, add)?;
+  Ok(aggregates)
+}
+
+// Add or remove a message length, call before the message itself is written or removed
+fn track_message(storage: &mut dyn Storage, length: u64, add: bool) -> StdResult<()> {
+  load_aggregates(storage)?;
+  adjust_index(&MESSAGE_LENGTHS, storage, length, add)
+}
+
+fn index_gas_extrema(storage: &mut dyn Storage, run: &TestRunStats, add: bool) -> StdResult<()> {
+  if let Some(min_gas) = run.min_gas {
+      adjust_index(&RUN_MIN_GAS, storage, min_gas.u128(), add)?;
+  }
+  if let Some(max_gas) = run.max_gas {
+      adjust_index(&RUN_MAX_GAS, storage, max_gas.u128(), add)?;
+  }
+  Ok(())
+}
+
+// Bump the count stored under `key`, dropping the entry once it reaches zero
+fn adjust_index<K>(index: &Map<'static, K, u64>, storage: &mut dyn Storage, key: K, add: bool) -> StdResult<()>
+where
+  K: PrimaryKey<'static> + Copy,
+{
+  let count = index.may_load(storage, key)?.unwrap_or(0);
+  match (add, count) {
+      (true, _) => index.save(storage, key, &(count + 1)),
+      (false, 0 | 1) => {
+          index.remove(storage, key);
+          Ok(())
+      }
+      (false, _) => index.save(storage, key, &(count - 1)),
+  }
+}
+
+// Collect up to `limit` keys in order, restricted to those starting with `prefix`
+fn prefixed_keys<T>(
+  map: &Map<&str, T>,
+  storage: &dyn Storage,
+  prefix: Option<&str>,
+  limit: usize,
+) -> StdResult<Vec<String>>
+where
+  T: Serialize + serde::de::DeserializeOwned,
+{
+  let start = prefix.map(Bound::inclusive);
+  map.keys(storage, start, None, cosmwasm_std::Order::Ascending)
+      .take_while(|key| match (key, prefix) {
+          (Ok(key), Some(prefix)) => key.starts_with(prefix),
+          _ => true,
+      })
+      .take(limit)
+      .collect()
+}
+
+// Look up a previously used idempotency key, returning a replay response if it was seen
+// A key whose message has since been removed is treated as unused so the retry is written
+fn idempotent_replay(
+  storage: &dyn Storage,
+  action: &str,
+  idempotency_key: Option<&str>,
+) -> Result<Option<Response>, ContractError> {
+  let existing_id = match idempotency_key {
+      Some(key) if key.len() > MAX_IDEMPOTENCY_KEY_LENGTH => {
+          return Err(ContractError::IdempotencyKeyTooLong {
+              length: key.len() as u64,
+              max: MAX_IDEMPOTENCY_KEY_LENGTH as u64,
+          });
+      }
+      Some(key) => IDEMPOTENCY_KEYS.may_load(storage, key)?,
+      None => None,
+  };
+  let existing_id = match existing_id {
+      Some(id) if MESSAGES.has(storage, &id) => Some(id),
+      _ => None,
+  };
+
+  existing_id
+      .map(|id| {
+          Ok(Response::new()
+              .set_data(to_json_binary(&StoreResult { id: id.clone() })?)
+              .add_attribute("action", action)
+              .add_attribute("id", id)
+              .add_attribute("idempotent_replay", "true"))
+      })
+      .transpose()
+}
+
+// Record the idempotency key a message was stored under, in both directions
+fn remember_idempotency_key(storage: &mut dyn Storage, key: &str, id: &str) -> StdResult<()> {
+  IDEMPOTENCY_KEYS.save(storage, key, &id.to_string())?;
+  IDEMPOTENCY_IDS.save(storage, id, &key.to_string())
+}
+
+// Drop the idempotency key of a message that was removed or overwritten
+fn forget_idempotency_key(storage: &mut dyn Storage, id: &str) -> StdResult<()> {
+  if let Some(key) = IDEMPOTENCY_IDS.may_load(storage, id)? {
+      IDEMPOTENCY_KEYS.remove(storage, &key);
+      IDEMPOTENCY_IDS.remove(storage, id);
+  }
+  Ok(())
+}
+
+// Find a stored message with the given content hash, ignoring entries whose
+// message has since been removed or overwritten
+fn find_duplicate(storage: &dyn Storage, hash: &[u8]) -> StdResult<Option<String>> {
+  let Some(id) = CONTENT_HASHES.may_load(storage, hash)? else {
+      return Ok(None);
+  };
+  
+  let unchanged = MESSAGES
+      .may_load(storage, &id)?
+      .map(|message| message.decoded_content())
+      .transpose()?
+      .map_or(false, |content| Sha256::digest(content.as_bytes()).as_slice() == hash);
+  Ok(unchanged.then_some(id))
+}
+
+/// Store msg with actual length
+#[allow(clippy::too_many_arguments)]
+pub fn execute_store_message(
+  deps: DepsMut,
+  env: Env,
+  _info: MessageInfo,
+  content: String,
+  idempotency_key: Option<String>,
+  gas_used: Option<Uint128>,
+  dedup: bool,
+  compress: bool,
+) -> Result<Response, ContractError> {
+  if let Some(res) = idempotent_replay(deps.storage, "store_message", idempotency_key.as_deref())? {
+      return Ok(res);
+  }
+  
+  // Hashing is skipped without dedup so plain stores measure only the write
+  let hash = dedup.then(|| Sha256::digest(content.as_bytes()));
+  if let Some(hash) = &hash {
+      if let Some(id) = find_duplicate(deps.storage, hash)? {
+          return Ok(Response::new()
+              .set_data(to_json_binary(&StoreResult { id: id.clone() })?)
+              .add_attribute("action", "store_message")
+              .add_attribute("id", id)
+              .add_attribute("already_exists", "true"));
+      }
+  }
+
+  // Validate msg size
+  let config = load_config(deps.storage)?;
+  let length = content.len() as u64;
+  if length > config.max_message_size {
+      return Err(ContractError::MessageTooLarge { 
+          size: length, 
+          max: config.max_message_size 
+      });
+  }
+
+  let id = format!("msg_{}", env.block.height);
+  
+  // Only keep the encoding if it actually saves space, length stays the original size
+  let encoded = compress
+      .then(|| Binary::from(rle_encode(content.as_bytes())).to_base64())
+      .filter(|encoded| encoded.len() < content.len());
+  let compressed = encoded.is_some();
+
+  let message = StoredMessage {
+      content: encoded.unwrap_or(content),
+      length,
+      stored_at: env.block.time.seconds(),
+      stored_at_height: env.block.height,
+      gas_used,
+      archived: false,
+      compressed,
+  };
+
+  let evicted = save_message(deps.storage, &id, &message)?;
+
+  if let Some(key) = idempotency_key {
+      remember_idempotency_key(deps.storage, &key, &id)?;
+  }
+  
+  if let Some(hash) = &hash {
+      CONTENT_HASHES.save(deps.storage, hash, &id)?;
+  }
+
+  Ok(Response::new()
+      .set_data(to_json_binary(&StoreResult { id: id.clone() })?)
+      .add_attribute("action", "store_message")
+      .add_attribute("id", id)
+      .add_attribute("length", length.to_string())
+      .add_attribute("stored_at", message.stored_at.to_string())
+      .add_attributes(evicted.map(|evicted_id| ("evicted_id", evicted_id)))
+      .add_attributes(hash.map(|_| ("already_exists", "false")))
+      .add_attributes(compress.then(|| ("stored_bytes", message.content.len().to_string()))))
+}
+
+// Replace a stored message's content under the same id (admin only)
+pub fn execute_update_message(
+  deps: DepsMut,
+  env: Env,
+  info: MessageInfo,
+  id: String,
+  content: String,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  
+  // Only owner can edit messages
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  let mut message = MESSAGES
+      .may_load(deps.storage, &id)?
+      .ok_or(ContractError::NoData {})?;
+  
+  let config = load_config(deps.storage)?;
+  let length = content.len() as u64;
+  if length > config.max_message_size {
+      return Err(ContractError::MessageTooLarge { 
+          size: length, 
+          max: config.max_message_size 
+      });
+  }
+  
+  track_message(deps.storage, message.length, false)?;
+  track_message(deps.storage, length, true)?;
+  message.content = content;
+  message.compressed = false;
+  message.length = length;
+  message.stored_at = env.block.time.seconds();
+  message.stored_at_height = env.block.height;
+  
+  MESSAGES.save(deps.storage, &id, &message)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "update_message")
+      .add_attribute("id", id)
+      .add_attribute("length", length.to_string()))
+}
+
+// Append to a stored message's content under the same id (admin only)
+pub fn execute_append_to_message(
+  deps: DepsMut,
+  env: Env,
+  info: MessageInfo,
+  id: String,
+  suffix: String,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  let mut message = MESSAGES
+      .may_load(deps.storage, &id)?
+      .ok_or(ContractError::NoData {})?;
+  
+  let mut content = message.decoded_content()?;
+  content.push_str(&suffix);
+  
+  let config = load_config(deps.storage)?;
+  let length = content.len() as u64;
+  if length > config.max_message_size {
+      return Err(ContractError::MessageTooLarge { 
+          size: length, 
+          max: config.max_message_size 
+      });
+  }
+  
+  track_message(deps.storage, message.length, false)?;
+  track_message(deps.storage, length, true)?;
+  message.content = content;
+  message.compressed = false;
+  message.length = length;
+  message.stored_at = env.block.time.seconds();
+  message.stored_at_height = env.block.height;
+  
+  MESSAGES.save(deps.storage, &id, &message)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "append_to_message")
+      .add_attribute("id", id)
+      .add_attribute("appended", suffix.len().to_string())
+      .add_attribute("length", length.to_string()))
+}
+
+// Set or clear a message's archived flag (admin only)
+pub fn execute_archive_message(
+  deps: DepsMut,
+  _env: Env,
+  info: MessageInfo,
+  id: String,
+  archived: bool,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  let mut message = MESSAGES
+      .may_load(deps.storage, &id)?
+      .ok_or_else(|| ContractError::MessageNotFound { id: id.clone() })?;
+  message.archived = archived;
+  MESSAGES.save(deps.storage, &id, &message)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "archive_message")
+      .add_attribute("id", id)
+      .add_attribute("archived", archived.to_string()))
+}
+
+// Store a message with a specific target length
+pub fn execute_store_fixed_length(
+  deps: DepsMut,
+  env: Env,
+  _info: MessageInfo,
+  content: String,
+  target_length: u64,
+  idempotency_key: Option<String>,
+) -> Result<Response, ContractError> {
+  if let Some(res) = idempotent_replay(deps.storage, "store_fixed_length", idempotency_key.as_deref())? {
+      return Ok(res);
+  }
+
+  // Validate target length
+  let config = load_config(deps.storage)?;
+  if target_length > config.max_message_size {
+      return Err(ContractError::MessageTooLarge { 
+          size: target_length, 
+          max: config.max_message_size 
+      });
+  }
+  
+  let id = format!("msg_{}_{}", env.block.height, target_length);
+  
+  // Adjust content to match target length
+  let adjusted_content = if content.len() as u64 > target_length {
+      // Truncate if too long
+      content.chars().take(target_length as usize).collect()
+  } else {
+      // Pad with spaces if too short
+      let padding = " ".repeat((target_length as usize).saturating_sub(content.len()));
+      format!("{}{}", content, padding)
+  };
+  
+  let actual_length = adjusted_content.len() as u64;
+
+  // Verify adjustment worked correctly
+  if actual_length != target_length {
+      return Err(ContractError::InvalidMessageLength { 
+          length: actual_length, 
+          expected: target_length 
+      });
+  }
+
+  let message = StoredMessage {
+      content: adjusted_content,
+      length: actual_length,
+      stored_at: env.block.time.seconds(),
+      stored_at_height: env.block.height,
+      gas_used: None,
+      archived: false,
+      compressed: false,
+  };
+
+  let evicted = save_message(deps.storage, &id, &message)?;
+
+  if let Some(key) = idempotency_key {
+      remember_idempotency_key(deps.storage, &key, &id)?;
+  }
+
+  Ok(Response::new()
+      .set_data(to_json_binary(&StoreResult { id: id.clone() })?)
+      .add_attribute("action", "store_fixed_length")
+      .add_attribute("id", id)
+      .add_attribute("length", actual_length.to_string())
+      .add_attribute("stored_at", message.stored_at.to_string())
+      .add_attributes(evicted.map(|evicted_id| ("evicted_id", evicted_id))))
+}
+
+// Record test run statistics
+#[allow(clippy::too_many_arguments)]
+pub fn execute_record_test_run(
+  deps: DepsMut,
+  env: Env,
+  info: MessageInfo,
+  run_id: String,
+  count: u64,
+  gas: Uint128,
+  avg_gas: Option<Uint128>,
+  chain: String,
+  tx_proofs: Option<Vec<String>>,
+  byte_length: Option<u64>,
+  overwrite: Option<bool>,
+  code_size_bytes: Option<u64>,
+  bytes: u64,
+  min_gas: Option<Uint128>,
+  max_gas: Option<Uint128>,
+  median_gas: Option<Uint128>,
+  samples: Vec<GasSample>,
+  gas_price: Option<Decimal>,
+  fee_denom: Option<String>,
+  tags: Option<Vec<String>>,
+  note: Option<String>,
+  (started_at, finished_at): (Option<u64>, Option<u64>),
+  node_version: Option<String>,
+  message_ids: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+  validate_run_id(&run_id)?;
+  let config = load_config(deps.storage)?;
+  let avg_gas = resolve_avg_gas(&config, count, gas, avg_gas, bytes, byte_length)?;
+  validate_test_run(&config, &run_id, &chain, count, gas, avg_gas)?;
+  validate_gas_distribution(gas, min_gas, median_gas, max_gas)?;
+  validate_gas_price(gas_price, fee_denom.as_deref())?;
+  if samples.len() > MAX_GAS_SAMPLES {
+      return Err(ContractError::TooManySamples {
+          count: samples.len() as u64,
+          max: MAX_GAS_SAMPLES as u64,
+      });
+  }
+  let tx_proofs = tx_proofs.unwrap_or_default();
+  validate_tx_proofs(&tx_proofs, &config)?;
+  let tags = tags.unwrap_or_default();
+  validate_run_metadata(&tags, note.as_deref(), node_version.as_deref())?;
+  validate_run_timing(started_at, finished_at, env.block.time.seconds())?;
+  
+  // Only owner or recorders can record test runs, unless recording is open
+  // Renouncing still freezes the runs either way
+  let state = STATE.load(deps.storage)?;
+  let open = config.open_recording && !state.renounced;
+  if !open && !state.can_record(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  // Reject re-recording an existing run on this chain unless overwrite was requested
+  if TEST_RUNS.has(deps.storage, (&normalize_chain_id(&chain), &run_id)) && !overwrite.unwrap_or(false) {
+      return Err(ContractError::RunExists { run_id });
+  }
+  
+  let coverage = message_ids
+      .map(|ids| verify_message_coverage(deps.storage, &ids, count))
+      .transpose()?;
+  let gas_before = load_aggregates(deps.storage)?.total_gas;
+  
+  let mut test_run = TestRunStats {
+      timestamp: env.block.time.seconds(),
+      message_count: count,
+      total_gas: gas,
+      avg_gas_per_byte: avg_gas,
+      chain_id: normalize_chain_id(&chain),
+      raw_chain_id: Some(chain).filter(|raw| *raw != normalize_chain_id(raw)),
+      tx_proofs,
+      byte_length,
+      code_size_bytes,
+      total_bytes: bytes,
+      min_gas,
+      max_gas,
+      median_gas,
+      samples,
+      gas_price,
+      fee_denom,
+      tags,
+      note,
+      seq: 0,
+      started_at,
+      finished_at,
+      node_version,
+      contract_version: get_contract_version(deps.storage).ok().map(|version| version.version),
+      coverage,
+  };
+  let cumulative_gas = save_test_run(deps.storage, &run_id, &mut test_run)?;
+  
+  // Past the budget every record says so, the event marks the run that crossed it
+  let mut response = Response::new()
+      .add_attribute("action", "record_test_run")
+      .add_attribute("run_id", &run_id)
+      .add_attribute("count", count.to_string())
+      .add_attribute("gas", gas.to_string())
+      .add_attribute("tx_count", test_run.tx_proofs.len().to_string())
+      .add_attribute("cumulative_gas", cumulative_gas.to_string());
+  if let Some(budget) = config.gas_budget {
+      let exceeded = cumulative_gas > budget;
+      response = response.add_attribute("budget_exceeded", exceeded.to_string());
+      if exceeded && gas_before <= budget {
+          response = response.add_event(
+              Event::new("gas_budget_exceeded")
+                  .add_attribute("run_id", run_id)
+                  .add_attribute("budget", budget.to_string())
+                  .add_attribute("cumulative_gas", cumulative_gas.to_string()),
+          );
+      }
+  }
+  
+  Ok(response)
+}
+
+// Record each entry as its own RecordTestRun, the run count grows once per entry
+pub fn execute_record_test_run_batch(
+  mut deps: DepsMut,
+  env: Env,
+  info: MessageInfo,
+  runs: Vec<RecordEntry>,
+) -> Result<Response, ContractError> {
+  if runs.len() > MAX_RECORD_BATCH {
+      return Err(ContractError::LimitExceeded {
+          requested: runs.len() as u64,
+          max: MAX_RECORD_BATCH as u64,
+      });
+  }
+  
+  // Ids are unique per chain, so only the same id twice on one chain clashes
+  let mut seen = BTreeSet::new();
+  for entry in &runs {
+      validate_run_id(&entry.run_id)?;
+      if !seen.insert((normalize_chain_id(&entry.chain), entry.run_id.as_str())) {
+          return Err(ContractError::InvalidRunId(format!(
+              "{} appears more than once for chain {} in the batch", entry.run_id, entry.chain
+          )));
+      }
+  }
+  
+  // Each entry's attributes and events are passed through, so a batch reports
+  // the same run ids and budget warnings as recording the runs one by one
+  let mut response = Response::new()
+      .add_attribute("action", "record_test_run_batch")
+      .add_attribute("count", runs.len().to_string());
+  for entry in runs {
+      let RecordEntry {
+          run_id, count, gas, avg_gas, chain, tx_proofs, tx_hashes, byte_length, overwrite, code_size_bytes, bytes,
+          min_gas, max_gas, median_gas, samples, gas_price, fee_denom, tags, note, started_at, finished_at, node_version,
+          message_ids,
+      } = entry;
+      let recorded = execute_record_test_run(
+          deps.branch(), env.clone(), info.clone(), run_id, count, gas, avg_gas, chain, tx_hashes.or(tx_proofs),
+          byte_length, overwrite, code_size_bytes, bytes, min_gas, max_gas, median_gas, samples, gas_price,
+          fee_denom, tags, note, (started_at, finished_at), node_version, message_ids,
+      )?;
+      response = response
+          .add_attributes(recorded.attributes.into_iter().filter(|attr| attr.key != "action"))
+          .add_events(recorded.events);
+  }
+  
+  Ok(response)
+}
+
+// Amend an existing test run, the run count is left as is
+#[allow(clippy::too_many_arguments)]
+pub fn execute_update_test_run(
+  deps: DepsMut,
+  _env: Env,
+  info: MessageInfo,
+  run_id: String,
+  count: u64,
+  gas: Uint128,
+  avg_gas: Option<Uint128>,
+  chain: String,
+  tx_proofs: Option<Vec<String>>,
+  byte_length: Option<u64>,
+  code_size_bytes: Option<u64>,
+  bytes: u64,
+  min_gas: Option<Uint128>,
+  max_gas: Option<Uint128>,
+  median_gas: Option<Uint128>,
+  samples: Vec<GasSample>,
+  gas_price: Option<Decimal>,
+  fee_denom: Option<String>,
+) -> Result<Response, ContractError> {
+  let config = load_config(deps.storage)?;
+  validate_run_id(&run_id)?;
+  let avg_gas = resolve_avg_gas(&config, count, gas, avg_gas, bytes, byte_length)?;
+  validate_test_run(&config, &run_id, &chain, count, gas, avg_gas)?;
+  validate_gas_distribution(gas, min_gas, median_gas, max_gas)?;
+  validate_gas_price(gas_price, fee_denom.as_deref())?;
+  if samples.len() > MAX_GAS_SAMPLES {
+      return Err(ContractError::TooManySamples {
+          count: samples.len() as u64,
+          max: MAX_GAS_SAMPLES as u64,
+      });
+  }
+  let tx_proofs = tx_proofs.unwrap_or_default();
+  validate_tx_proofs(&tx_proofs, &config)?;
+  
+  let state = STATE.load(deps.storage)?;
+  if !state.can_record(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  // The timestamp, tags, note, timing and versions aren't part of the update, they carry
+  // over so an amendment doesn't move the run in time order
+  let Some(previous) = TEST_RUNS.may_load(deps.storage, (&normalize_chain_id(&chain), &run_id))? else {
+      return Err(ContractError::RunNotFound { run_id });
+  };
+  
+  let mut test_run = TestRunStats {
+      timestamp: previous.timestamp,
+      message_count: count,
+      total_gas: gas,
+      avg_gas_per_byte: avg_gas,
+      chain_id: normalize_chain_id(&chain),
+      raw_chain_id: Some(chain).filter(|raw| *raw != normalize_chain_id(raw)),
+      tx_proofs,
+      byte_length,
+      code_size_bytes,
+      total_bytes: bytes,
+      min_gas,
+      max_gas,
+      median_gas,
+      samples,
+      gas_price,
+      fee_denom,
+      tags: previous.tags,
+      note: previous.note,
+      seq: 0,
+      started_at: previous.started_at,
+      finished_at: previous.finished_at,
+      node_version: previous.node_version,
+      contract_version: previous.contract_version,
+      // Coverage was checked against the old count, it only stays while that still holds
+      coverage: previous.coverage.filter(|coverage| coverage.messages == count),
+  };
+  let cumulative_gas = save_test_run(deps.storage, &run_id, &mut test_run)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "update_test_run")
+      .add_attribute("run_id", run_id)
+      .add_attribute("count", count.to_string())
+      .add_attribute("gas", gas.to_string())
+      .add_attribute("tx_count", test_run.tx_proofs.len().to_string())
+      .add_attribute("cumulative_gas", cumulative_gas.to_string()))
+}
+
+// Canonical chain id form used for storage and chain filters
+fn normalize_chain_id(chain: &str) -> String {
+  chain.trim().to_lowercase()
+}
+
+// Whether a run belongs to the chain filter, runs stored before normalization are matched too
+fn on_chain(run: &TestRunStats, chain: Option<&str>) -> bool {
+  chain.map_or(true, |chain| normalize_chain_id(&run.chain_id) == normalize_chain_id(chain))
+}
+
+// Shared input checks for recording and updating runs
+fn validate_test_run(
+  config: &Config,
+  run_id: &str,
+  chain: &str,
+  count: u64,
+  gas: Uint128,
+  avg_gas: Uint128,
+) -> Result<(), ContractError> {
+  // Validate run_id format
+  if run_id.trim().is_empty() {
+      return Err(ContractError::InvalidRunId("Run ID cannot be empty".into()));
+  }
+
+  // Validate chain id format
+  if normalize_chain_id(chain).is_empty() {
+      return Err(ContractError::InvalidChainId("Chain ID cannot be empty".into()));
+  }
+
+  // Validate gas value
+  if gas.is_zero() && count > 0 {
+      return Err(ContractError::InvalidGasValue("Gas cannot be zero for non-empty test runs".into()));
+  }
+  
+  // Per-byte gas can't exceed the total, and every message costs at least 1 gas
+  if config.strict_validation && count > 0 && !avg_gas.is_zero() {
+      if avg_gas > gas {
+          return Err(ContractError::InvalidGasValue(format!(
+              "avg_gas {} exceeds total gas {}", avg_gas, gas
+          )));
+      }
+      if gas.u128() / (count as u128) < 1 {
+          return Err(ContractError::InvalidGasValue(format!(
+              "gas {} is less than 1 per message for {} messages", gas, count
+          )));
+      }
+  }
+  
+  // Configured sanity bounds catch avg_gas off by orders of magnitude
+  if let Some(min) = config.min_gas_per_byte {
+      if avg_gas < min {
+          return Err(ContractError::InvalidGasValue(format!(
+              "avg_gas {} is below min_gas_per_byte {}", avg_gas, min
+          )));
+      }
+  }
+  if let Some(max) = config.max_gas_per_byte {
+      if avg_gas > max {
+          return Err(ContractError::InvalidGasValue(format!(
+              "avg_gas {} is above max_gas_per_byte {}", avg_gas, max
+          )));
+      }
+  }
+  
+  Ok(())
+}
+
+// avg_gas is per byte, so it is derived from the reported byte total when omitted
+// A supplied value must be within avg_gas_tolerance_bps of that total
+fn resolve_avg_gas(
+  config: &Config,
+  count: u64,
+  gas: Uint128,
+  avg_gas: Option<Uint128>,
+  bytes: u64,
+  byte_length: Option<u64>,
+) -> Result<Uint128, ContractError> {
+  let total_bytes = if bytes > 0 {
+      bytes
+  } else {
+      byte_length.unwrap_or(0).saturating_mul(count)
+  };
+  let derived = match (count, total_bytes) {
+      (0, _) => Some(Uint128::zero()),
+      (_, 0) => None,
+      (_, total_bytes) => Some(gas / Uint128::from(total_bytes)),
+  };
+  
+  match (avg_gas, derived) {
+      (Some(avg_gas), Some(derived)) => {
+          // Integer division leaves derived up to 1 below the true average
+          let tolerance = config.avg_gas_tolerance_bps;
+          let allowed = (derived.u128().saturating_mul(tolerance as u128) / 10_000).max(1);
+          if avg_gas.u128().abs_diff(derived.u128()) > allowed {
+              return Err(ContractError::InvalidGasValue(format!(
+                  "avg_gas {} is more than {} bps from gas / bytes = {}", avg_gas, tolerance, derived
+              )));
+          }
+          Ok(avg_gas)
+      }
+      (Some(avg_gas), None) => Ok(avg_gas),
+      (None, Some(derived)) => Ok(derived),
+      (None, None) => Err(ContractError::InvalidGasValue(
+          "avg_gas is required when neither bytes nor byte_length is given".into(),
+      )),
+  }
+}
+
+// Reported values must satisfy min <= median <= max <= total gas
+fn validate_gas_distribution(
+  gas: Uint128,
+  min_gas: Option<Uint128>,
+  median_gas: Option<Uint128>,
+  max_gas: Option<Uint128>,
+) -> Result<(), ContractError> {
+  let ordered: Vec<(&str, Uint128)> = [
+      ("min_gas", min_gas),
+      ("median_gas", median_gas),
+      ("max_gas", max_gas),
+      ("gas", Some(gas)),
+  ]
+  .into_iter()
+  .filter_map(|(name, value)| value.map(|value| (name, value)))
+  .collect();
+  
+  for pair in ordered.windows(2) {
+      let ((lower_name, lower), (upper_name, upper)) = (pair[0], pair[1]);
+      if lower > upper {
+          return Err(ContractError::InvalidGasValue(format!(
+              "{} {} exceeds {} {}", lower_name, lower, upper_name, upper
+          )));
+      }
+  }
+  
+  Ok(())
+}
+
+// A gas price is only meaningful with the denom it was paid in
+fn validate_gas_price(gas_price: Option<Decimal>, fee_denom: Option<&str>) -> Result<(), ContractError> {
+  match (gas_price, fee_denom) {
+      (Some(_), Some(denom)) if denom.trim().is_empty() => {
+          Err(ContractError::InvalidGasPrice("fee_denom cannot be empty".into()))
+      }
+      (Some(_), None) => Err(ContractError::InvalidGasPrice("gas_price requires fee_denom".into())),
+      (None, Some(_)) => Err(ContractError::InvalidGasPrice("fee_denom requires gas_price".into())),
+      _ => Ok(()),
+  }
+}
+
+// Save a run under its chain and keep test_run_count and the aggregates in sync
+// Replacing an existing run doesn't add to the count and keeps its seq, returns the new total gas
+fn save_test_run(storage: &mut dyn Storage, run_id: &str, run: &mut TestRunStats) -> StdResult<Uint128> {
+  let previous = TEST_RUNS.may_load(storage, (&run.chain_id, run_id))?;
+  
+  let mut state = STATE.load(storage)?;
+  match &previous {
+      Some(previous) => {
+          track_run(storage, previous, false)?;
+      }
+      None => state.test_run_count += 1,
+  }
+  run.seq = match &previous {
+      Some(previous) if previous.seq > 0 => previous.seq,
+      _ => assign_run_seq(storage, &mut state, &run.chain_id, run_id)?,
+  };
+  let aggregates = track_run(storage, run, true)?;
+  TEST_RUNS.save(storage, (&run.chain_id, run_id), run)?;
+  index_run_chain(storage, run_id, &run.chain_id, true)?;
+  // A run that keeps its timestamp can't change which run is latest
+  if previous.as_ref().map(|previous| previous.timestamp) != Some(run.timestamp) {
+      if let Some(previous) = &previous {
+          RUN_TIMES.remove(storage, (previous.timestamp, &run.chain_id, run_id));
+      }
+      RUN_TIMES.save(storage, (run.timestamp, &run.chain_id, run_id), &Empty {})?;
+      index_latest_run(storage, &run.chain_id, run_id, Some(run.timestamp))?;
+      state.last_test_timestamp = Some(run.timestamp);
+  }
+  
+  STATE.save(storage, &state)?;
+  
+  Ok(aggregates.total_gas)
+}
+
+// Every referenced message must be stored, listed once, and account for the whole count
+fn verify_message_coverage(storage: &dyn Storage, ids: &[String], count: u64) -> Result<MessageCoverage, ContractError> {
+  if ids.len() > MAX_RUN_MESSAGE_IDS {
+      return Err(ContractError::LimitExceeded {
+          requested: ids.len() as u64,
+          max: MAX_RUN_MESSAGE_IDS as u64,
+      });
+  }
+  
+  let mut seen = BTreeSet::new();
+  let mut bytes = 0u64;
+  for id in ids {
+      if !seen.insert(id.as_str()) {
+          return Err(ContractError::InvalidRunMetadata(format!("message id {} is listed more than once", id)));
+      }
+      let message = MESSAGES
+          .may_load(storage, id)?
+          .ok_or_else(|| ContractError::MessageNotFound { id: id.clone() })?;
+      bytes = bytes.saturating_add(message.length);
+  }
+  
+  if ids.len() as u64 != count {
+      return Err(ContractError::MessageCountMismatch { count, referenced: ids.len() as u64 });
+  }
+  Ok(MessageCoverage { messages: count, bytes })
+}
+
+// Hand out the next run sequence number and index it
+fn assign_run_seq(storage: &mut dyn Storage, state: &mut State, chain: &str, run_id: &str) -> StdResult<u64> {
+  state.last_run_seq += 1;
+  RUN_SEQS.save(storage, state.last_run_seq, &(chain.to_string(), run_id.to_string()))?;
+  Ok(state.last_run_seq)
+}
+
+// New run ids stay within [A-Za-z0-9_-] so they are safe in CSV exports and URLs
+// Runs recorded before the rule keep their ids but must be re-recorded under a valid one
+// to be amended, UpdateTestRun applies it too
+fn validate_run_id(run_id: &str) -> Result<(), ContractError> {
+  if run_id.trim().is_empty() {
+      return Err(ContractError::InvalidRunId("Run ID cannot be empty".into()));
+  }
+  if run_id.len() > MAX_RUN_ID_LENGTH {
+      return Err(ContractError::InvalidRunId(format!(
+          "Run ID is {} bytes, maximum is {}", run_id.len(), MAX_RUN_ID_LENGTH
+      )));
+  }
+  if let Some(invalid) = run_id.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '-')) {
+      return Err(ContractError::InvalidRunId(format!(
+          "Run ID contains {:?}, only letters, digits, '_' and '-' are allowed", invalid
+      )));
+  }
+  Ok(())
+}
+
+// Bound the number and size of tags, the note and node_version, naming the offending field
+fn validate_run_metadata(tags: &[String], note: Option<&str>, node_version: Option<&str>) -> Result<(), ContractError> {
+  if tags.len() > MAX_RUN_TAGS {
+      return Err(ContractError::InvalidRunMetadata(format!(
+          "{} tags exceeds maximum of {}", tags.len(), MAX_RUN_TAGS
+      )));
+  }
+  for (index, tag) in tags.iter().enumerate() {
+      if tag.trim().is_empty() {
+          return Err(ContractError::InvalidRunMetadata(format!("tag {} is empty", index)));
+      }
+      if tag.len() > MAX_RUN_TAG_LENGTH {
+          return Err(ContractError::InvalidRunMetadata(format!(
+              "tag {} (\"{}\") is {} bytes, maximum is {}", index, tag, tag.len(), MAX_RUN_TAG_LENGTH
+          )));
+      }
+  }
+  if let Some(note) = note {
+      if note.len() > MAX_RUN_NOTE_LENGTH {
+          return Err(ContractError::InvalidRunMetadata(format!(
+              "note is {} bytes, maximum is {}", note.len(), MAX_RUN_NOTE_LENGTH
+          )));
+      }
+  }
+  if let Some(node_version) = node_version {
+      if node_version.trim().is_empty() {
+          return Err(ContractError::InvalidRunMetadata("node_version is empty".to_string()));
+      }
+      if node_version.len() > MAX_NODE_VERSION_LENGTH {
+          return Err(ContractError::InvalidRunMetadata(format!(
+              "node_version is {} bytes, maximum is {}", node_version.len(), MAX_NODE_VERSION_LENGTH
+          )));
+      }
+  }
+  Ok(())
+}
+
+// Reported times can't be in the future, and the sweep can't end before it started
+fn validate_run_timing(started_at: Option<u64>, finished_at: Option<u64>, now: u64) -> Result<(), ContractError> {
+  for (name, time) in [("started_at", started_at), ("finished_at", finished_at)] {
+      if let Some(time) = time.filter(|time| *time > now) {
+          return Err(ContractError::InvalidRunMetadata(format!(
+              "{} {} is after the block time {}", name, time, now
+          )));
+      }
+  }
+  if let (Some(started_at), Some(finished_at)) = (started_at, finished_at) {
+      if finished_at < started_at {
+          return Err(ContractError::InvalidRunMetadata(format!(
+              "finished_at {} is before started_at {}", finished_at, started_at
+          )));
+      }
+  }
+  Ok(())
+}
+
+// Check proof count and that each proof is a non-empty, bounded string
+// With hex_tx_proofs every proof must also be a 64 character hex tx hash
+fn validate_tx_proofs(proofs: &[String], config: &Config) -> Result<(), ContractError> {
+  if proofs.len() > MAX_TX_PROOFS {
+      return Err(ContractError::TooManyProofs {
+          count: proofs.len() as u64,
+          max: MAX_TX_PROOFS as u64,
+      });
+  }
+  
+  // Oversized proof lists make every page that returns the run too large to query
+  let size = proofs.iter().map(|proof| proof.len() as u64).sum::<u64>();
+  if size > config.max_proof_bytes {
+      return Err(ContractError::ProofTooLarge { size, max: config.max_proof_bytes });
+  }
+  
+  for proof in proofs {
+      if proof.trim().is_empty() {
+          return Err(ContractError::InvalidTxProof("Proof cannot be empty".into()));
+      }
+      if proof.len() > MAX_TX_PROOF_LENGTH {
+          return Err(ContractError::InvalidTxProof(format!(
+              "Proof exceeds {} characters", MAX_TX_PROOF_LENGTH
+          )));
+      }
+      if config.hex_tx_proofs && !(proof.len() == 64 && proof.chars().all(|c| c.is_ascii_hexdigit())) {
+          return Err(ContractError::InvalidTxProof(format!(
+              "{} is not a 64 character hex hash", proof
+          )));
+      }
+  }
+  
+  Ok(())
+}
+
+// Append proofs to an existing run as its batches confirm
+pub fn execute_append_tx_proofs(
+  deps: DepsMut,
+  _env: Env,
+  info: MessageInfo,
+  run_id: String,
+  proofs: Vec<String>,
+  chain: Option<String>,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  if !state.can_record(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  let (chain, mut run) = find_run(deps.storage, &run_id, chain.as_deref())?
+      .ok_or_else(|| ContractError::RunNotFound { run_id: run_id.clone() })?;
+  
+  run.tx_proofs.extend(proofs);
+  validate_tx_proofs(&run.tx_proofs, &load_config(deps.storage)?)?;
+  TEST_RUNS.save(deps.storage, (&chain, &run_id), &run)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "append_tx_proofs")
+      .add_attribute("run_id", run_id)
+      .add_attribute("tx_count", run.tx_proofs.len().to_string()))
+}
+
+// Clear all stored data (admin only)
+pub fn execute_clear_data(
+  deps: DepsMut,
+  env: Env,
+  info: MessageInfo,
+  limit: Option<u32>,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  
+  // Only owner can clear data
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  // Keys are read lazily so only the current batch is loaded
+  let batch = limit.map_or(usize::MAX, |limit| limit as usize);
+  
+  // Delete messages
+  let keys_to_remove: Vec<String> = MESSAGES
+      .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .take(batch)
+      .collect::<Result<Vec<_>, _>>()?;
+  
+  let bytes_freed = remove_messages(deps.storage, &keys_to_remove)?;
+  
+  // Delete test runs
+  let run_keys_to_remove: Vec<(String, String)> = TEST_RUNS
+      .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .take(batch)
+      .collect::<Result<Vec<_>, _>>()?;
+  
+  remove_test_runs(deps.storage, &run_keys_to_remove)?;
+  
+  // Sweep leftover idempotency keys and content hashes, e.g. keys that predate
+  // per-message tracking, only once every message is gone so live ones survive
+  let messages_left = MESSAGES.keys(deps.storage, None, None, cosmwasm_std::Order::Ascending).next().is_some();
+  if !messages_left {
+      let idem_keys_to_remove: Vec<String> = IDEMPOTENCY_KEYS
+          .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+          .take(batch)
+          .collect::<Result<Vec<_>, _>>()?;
+      
+      for key in idem_keys_to_remove {
+          IDEMPOTENCY_KEYS.remove(deps.storage, &key);
+      }
+      
+      let idem_ids_to_remove: Vec<String> = IDEMPOTENCY_IDS
+          .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+          .take(batch)
+          .collect::<Result<Vec<_>, _>>()?;
+      
+      for id in idem_ids_to_remove {
+          IDEMPOTENCY_IDS.remove(deps.storage, &id);
+      }
+      
+      let hashes_to_remove: Vec<Vec<u8>> = CONTENT_HASHES
+          .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+          .take(batch)
+          .collect::<Result<Vec<_>, _>>()?;
+      
+      for hash in hashes_to_remove {
+          CONTENT_HASHES.remove(deps.storage, &hash);
+      }
+  }
+  
+  let complete = !messages_left
+      && TEST_RUNS.keys(deps.storage, None, None, cosmwasm_std::Order::Ascending).next().is_none()
+      && IDEMPOTENCY_KEYS.keys(deps.storage, None, None, cosmwasm_std::Order::Ascending).next().is_none()
+      && IDEMPOTENCY_IDS.keys(deps.storage, None, None, cosmwasm_std::Order::Ascending).next().is_none()
+      && CONTENT_HASHES.keys(deps.storage, None, None, cosmwasm_std::Order::Ascending).next().is_none();
+  
+  // Update state but keep configuration
+  let mut updated_state = STATE.load(deps.storage)?;
+  if complete {
+      updated_state.test_run_count = 0;
+      updated_state.last_test_timestamp = Some(env.block.time.seconds());
+  }
+  
+  STATE.save(deps.storage, &updated_state)?;
+  
+  record_clear_event(deps.storage, &ClearEvent {
+      timestamp: env.block.time.seconds(),
+      messages_removed: keys_to_remove.len() as u64,
+      runs_removed: run_keys_to_remove.len() as u64,
+      bytes_freed,
+  })?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "clear_data")
+      .add_attribute("time", env.block.time.seconds().to_string())
+      .add_attribute("messages_removed", keys_to_remove.len().to_string())
+      .add_attribute("runs_removed", run_keys_to_remove.len().to_string())
+      .add_attribute("complete", complete.to_string()))
+}
+
+// Clear messages only, optionally by id prefix (admin only)
+pub fn execute_clear_messages(
+  deps: DepsMut,
+  env: Env,
+  info: MessageInfo,
+  prefix: Option<String>,
+  limit: Option<u32>,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  
+  // Only owner can clear data
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  let batch = limit.map_or(usize::MAX, |limit| limit as usize);
+  let keys = prefixed_keys(&MESSAGES, deps.storage, prefix.as_deref(), batch)?;
+  let bytes_freed = remove_messages(deps.storage, &keys)?;
+  
+  let complete = prefixed_keys(&MESSAGES, deps.storage, prefix.as_deref(), 1)?.is_empty();
+  
+  record_clear_event(deps.storage, &ClearEvent {
+      timestamp: env.block.time.seconds(),
+      messages_removed: keys.len() as u64,
+      runs_removed: 0,
+      bytes_freed,
+  })?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "clear_messages")
+      .add_attribute("removed", keys.len().to_string())
+      .add_attribute("complete", complete.to_string()))
+}
+
+// Clear test runs only, optionally by id prefix (admin only)
+pub fn execute_clear_test_runs(
+  deps: DepsMut,
+  env: Env,
+  info: MessageInfo,
+  prefix: Option<String>,
+  limit: Option<u32>,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  
+  // Only owner can clear data
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  // The batch counts run ids, every chain's run under an id goes together
+  let batch = limit.map_or(usize::MAX, |limit| limit as usize);
+  let mut keys = vec![];
+  for run_id in prefixed_keys(&RUN_CHAINS, deps.storage, prefix.as_deref(), batch)? {
+      for chain in RUN_CHAINS.load(deps.storage, &run_id)? {
+          keys.push((chain, run_id.clone()));
+      }
+  }
+  remove_test_runs(deps.storage, &keys)?;
+  
+  let complete = prefixed_keys(&RUN_CHAINS, deps.storage, prefix.as_deref(), 1)?.is_empty();
+  
+  record_clear_event(deps.storage, &ClearEvent {
+      timestamp: env.block.time.seconds(),
+      messages_removed: 0,
+      runs_removed: keys.len() as u64,
+      bytes_freed: 0,
+  })?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "clear_test_runs")
+      .add_attribute("removed", keys.len().to_string())
+      .add_attribute("complete", complete.to_string()))
+}
+
+// Delete one test run by id (admin only)
+pub fn execute_delete_test_run(
+  deps: DepsMut,
+  _env: Env,
+  info: MessageInfo,
+  run_id: String,
+  chain: Option<String>,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  let (chain, run) = find_run(deps.storage, &run_id, chain.as_deref())?
+      .ok_or_else(|| ContractError::RunNotFound { run_id: run_id.clone() })?;
+  remove_test_runs(deps.storage, &[(chain, run_id.clone())])?;
+  
+  // Record what was removed for auditing
+  Ok(Response::new()
+      .add_attribute("action", "delete_test_run")
+      .add_attribute("run_id", run_id)
+      .add_attribute("chain_id", run.chain_id)
+      .add_attribute("timestamp", run.timestamp.to_string()))
+}
+
+// Update configurable limits (admin only)
+#[allow(clippy::too_many_arguments)]
+pub fn execute_update_config(
+  deps: DepsMut,
+  _env: Env,
+  info: MessageInfo,
+  max_message_size: Option<u64>,
+  max_list_limit: Option<u32>,
+  max_runs_limit: Option<u32>,
+  max_messages: Option<u64>,
+  eviction: Option<bool>,
+  hex_tx_proofs: Option<bool>,
+  strict_validation: Option<bool>,
+  regression_threshold_bps: Option<u64>,
+  min_gas_per_byte: Option<Uint128>,
+  max_gas_per_byte: Option<Uint128>,
+  avg_gas_tolerance_bps: Option<u64>,
+  open_recording: Option<bool>,
+  max_proof_bytes: Option<u64>,
+  gas_budget: Option<Uint128>,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  
+  // Only owner can update config
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  let mut config = load_config(deps.storage)?;
+  
+  if let Some(size) = max_message_size {
+      if size == 0 || size > MAX_MESSAGE_SIZE_CEILING {
+          return Err(ContractError::InvalidConfig(format!(
+              "max_message_size must be between 1 and {}", MAX_MESSAGE_SIZE_CEILING
+          )));
+      }
+      config.max_message_size = size;
+  }
+  
+  for (name, limit, field) in [
+      ("max_list_limit", max_list_limit, &mut config.max_list_limit),
+      ("max_runs_limit", max_runs_limit, &mut config.max_runs_limit),
+  ] {
+      if let Some(limit) = limit {
+          if limit == 0 || limit > MAX_PAGE_LIMIT_CEILING {
+              return Err(ContractError::InvalidConfig(format!(
+                  "{} must be between 1 and {}", name, MAX_PAGE_LIMIT_CEILING
+              )));
+          }
+          *field = limit;
+      }
+  }
+  
+  if let Some(max) = max_messages {
+      config.max_messages = if max == 0 { None } else { Some(max) };
+  }
+  
+  if let Some(eviction) = eviction {
+      config.eviction = eviction;
+  }
+  
+  if let Some(hex_tx_proofs) = hex_tx_proofs {
+      config.hex_tx_proofs = hex_tx_proofs;
+  }
+  
+  if let Some(strict_validation) = strict_validation {
+      config.strict_validation = strict_validation;
+  }
+  
+  if let Some(threshold) = regression_threshold_bps {
+      config.regression_threshold_bps = threshold;
+  }
+  
+  if let Some(min) = min_gas_per_byte {
+      config.min_gas_per_byte = if min.is_zero() { None } else { Some(min) };
+  }
+  
+  if let Some(max) = max_gas_per_byte {
+      config.max_gas_per_byte = if max.is_zero() { None } else { Some(max) };
+  }
+  
+  if let Some(tolerance) = avg_gas_tolerance_bps {
+      if tolerance == 0 || tolerance > MAX_AVG_GAS_TOLERANCE_BPS {
+          return Err(ContractError::InvalidConfig(format!(
+              "avg_gas_tolerance_bps must be between 1 and {}", MAX_AVG_GAS_TOLERANCE_BPS
+          )));
+      }
+      config.avg_gas_tolerance_bps = tolerance;
+  }
+  
+  if let Some(open_recording) = open_recording {
+      config.open_recording = open_recording;
+  }
+  
+  if let Some(max) = max_proof_bytes {
+      if max == 0 {
+          return Err(ContractError::InvalidConfig("max_proof_bytes must be at least 1".into()));
+      }
+      config.max_proof_bytes = max;
+  }
+  
+  if let Some(budget) = gas_budget {
+      config.gas_budget = Some(budget).filter(|budget| !budget.is_zero());
+  }
+  
+  if let (Some(min), Some(max)) = (config.min_gas_per_byte, config.max_gas_per_byte) {
+      if min > max {
+          return Err(ContractError::InvalidConfig(format!(
+              "min_gas_per_byte {} exceeds max_gas_per_byte {}", min, max
+          )));
+      }
+  }
+  
+  CONFIG.save(deps.storage, &config)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "update_config")
+      .add_attribute("max_message_size", config.max_message_size.to_string())
+      .add_attribute("max_list_limit", config.max_list_limit.to_string())
+      .add_attribute("max_runs_limit", config.max_runs_limit.to_string())
+      .add_attribute("max_messages", config.max_messages.map_or("unlimited".to_string(), |max| max.to_string()))
+      .add_attribute("eviction", config.eviction.to_string())
+      .add_attribute("hex_tx_proofs", config.hex_tx_proofs.to_string())
+      .add_attribute("strict_validation", config.strict_validation.to_string())
+      .add_attribute("regression_threshold_bps", config.regression_threshold_bps.to_string())
+      .add_attribute("min_gas_per_byte", config.min_gas_per_byte.map_or("none".to_string(), |min| min.to_string()))
+      .add_attribute("max_gas_per_byte", config.max_gas_per_byte.map_or("none".to_string(), |max| max.to_string()))
+      .add_attribute("avg_gas_tolerance_bps", config.avg_gas_tolerance_bps.to_string())
+      .add_attribute("open_recording", config.open_recording.to_string())
+      .add_attribute("max_proof_bytes", config.max_proof_bytes.to_string())
+      .add_attribute("gas_budget", config.gas_budget.map_or("none".to_string(), |budget| budget.to_string())))
+}
+
+// Pause or resume the contract (admin only)
+pub fn execute_set_paused(
+  deps: DepsMut,
+  _env: Env,
+  info: MessageInfo,
+  paused: bool,
+) -> Result<Response, ContractError> {
+  let mut state = STATE.load(deps.storage)?;
+  
+  // Only owner can pause
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  state.paused = paused;
+  STATE.save(deps.storage, &state)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "set_paused")
+      .add_attribute("paused", paused.to_string()))
+}
+
+// Add or remove recorder addresses (admin only)
+pub fn execute_update_recorders(
+  deps: DepsMut,
+  _env: Env,
+  info: MessageInfo,
+  add: Vec<String>,
+  remove: Vec<String>,
+) -> Result<Response, ContractError> {
+  let mut state = STATE.load(deps.storage)?;
+  
+  // Only owner can manage recorders
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  for addr in &add {
+      let addr = deps.api.addr_validate(addr)?;
+      if !state.recorders.contains(&addr) {
+          state.recorders.push(addr);
+      }
+  }
+  
+  for addr in &remove {
+      let addr = deps.api.addr_validate(addr)?;
+      state.recorders.retain(|recorder| recorder != addr);
+  }
+  
+  STATE.save(deps.storage, &state)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "update_recorders")
+      .add_attribute("added", add.len().to_string())
+      .add_attribute("removed", remove.len().to_string())
+      .add_attribute("recorder_count", state.recorders.len().to_string()))
+}
+
+// Irreversibly renounce ownership (admin only)
+pub fn execute_renounce_ownership(
+  deps: DepsMut,
+  _env: Env,
+  info: MessageInfo,
+  confirm: String,
+) -> Result<Response, ContractError> {
+  let mut state = STATE.load(deps.storage)?;
+  
+  // Only owner can renounce
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  if confirm != "renounce" {
+      return Err(ContractError::InvalidConfirmation { expected: "renounce".into() });
+  }
+  
+  state.renounced = true;
+  STATE.save(deps.storage, &state)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "renounce_ownership")
+      .add_attribute("previous_owner", state.owner))
+}
+
+// Store a message and re-read it in the same execution
+pub fn execute_store_and_read(
+  deps: DepsMut,
+  env: Env,
+  _info: MessageInfo,
+  content: String,
+  reads: u32,
+) -> Result<Response, ContractError> {
+  if reads > MAX_READS {
+      return Err(ContractError::LimitExceeded { 
+          requested: reads as u64, 
+          max: MAX_READS as u64 
+      });
+  }
+  
+  let config = load_config(deps.storage)?;
+  let length = content.len() as u64;
+  if length > config.max_message_size {
+      return Err(ContractError::MessageTooLarge { 
+          size: length, 
+          max: config.max_message_size 
+      });
+  }
+  
+  let id = format!("msg_{}", env.block.height);
+  
+  let message = StoredMessage {
+      content,
+      length,
+      stored_at: env.block.time.seconds(),
+      stored_at_height: env.block.height,
+      gas_used: None,
+      archived: false,
+      compressed: false,
+  };
+  
+  let evicted = save_message(deps.storage, &id, &message)?;
+  
+  // Warm reads of the key just written
+  let mut last_read = None;
+  for _ in 0..reads {
+      last_read = Some(MESSAGES.load(deps.storage, &id)?);
+  }
+  
+  // Checksum the last read so the loads have an observable result
+  let checksum = last_read.map_or(0u32, |message| {
+      message.content.bytes().fold(0u32, |sum, b| sum.wrapping_add(b as u32))
+  });
+  
+  Ok(Response::new()
+      .add_attribute("action", "store_and_read")
+      .add_attribute("id", id)
+      .add_attribute("length", length.to_string())
+      .add_attribute("reads", reads.to_string())
+      .add_attribute("checksum", checksum.to_string())
+      .add_attributes(evicted.map(|evicted_id| ("evicted_id", evicted_id))))
+}
+
+// Read stored messages by id without mutating state
+pub fn execute_read_messages(
+  deps: DepsMut,
+  _env: Env,
+  _info: MessageInfo,
+  ids: Vec<String>,
+) -> Result<Response, ContractError> {
+  if ids.len() > MAX_READ_IDS {
+      return Err(ContractError::LimitExceeded { 
+          requested: ids.len() as u64, 
+          max: MAX_READ_IDS as u64 
+      });
+  }
+  
+  let mut bytes_read = 0u64;
+  for id in &ids {
+      let message = MESSAGES
+          .may_load(deps.storage, id)?
+          .ok_or_else(|| ContractError::MessageNotFound { id: id.clone() })?;
+      bytes_read += message.content.len() as u64;
+  }
+  
+  Ok(Response::new()
+      .add_attribute("action", "read_messages")
+      .add_attribute("reads", ids.len().to_string())
+      .add_attribute("bytes_read", bytes_read.to_string()))
+}
+
+// Range over stored messages without mutating state
+pub fn execute_iterate_messages(
+  deps: DepsMut,
+  _env: Env,
+  _info: MessageInfo,
+  limit: u32,
+  ascending: bool,
+) -> Result<Response, ContractError> {
+  if limit > MAX_ITERATE {
+      return Err(ContractError::LimitExceeded { 
+          requested: limit as u64, 
+          max: MAX_ITERATE as u64 
+      });
+  }
+  
+  let order = if ascending {
+      cosmwasm_std::Order::Ascending
+  } else {
+      cosmwasm_std::Order::Descending
+  };
+  
+  let mut visited = 0u64;
+  let mut bytes_seen = 0u64;
+  for item in MESSAGES.range(deps.storage, None, None, order).take(limit as usize) {
+      let (_, message) = item?;
+      visited += 1;
+      bytes_seen += message.length;
+  }
+  
+  Ok(Response::new()
+      .add_attribute("action", "iterate_messages")
+      .add_attribute("visited", visited.to_string())
+      .add_attribute("bytes_seen", bytes_seen.to_string()))
+}
+
+// Increment a named counter
+pub fn execute_increment_counter(
+  deps: DepsMut,
+  _env: Env,
+  _info: MessageInfo,
+  name: String,
+) -> Result<Response, ContractError> {
+  let value = CUSTOM_COUNTERS.may_load(deps.storage, &name)?.unwrap_or(0) + 1;
+  CUSTOM_COUNTERS.save(deps.storage, &name, &value)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "increment_counter")
+      .add_attribute("name", name)
+      .add_attribute("value", value.to_string()))
+}
+
+// Reset a named counter (admin only)
+pub fn execute_reset_counter(
+  deps: DepsMut,
+  _env: Env,
+  info: MessageInfo,
+  name: String,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  
+  // Only owner can reset counters
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  // Absent counters reset cleanly with a previous value of zero
+  let previous = CUSTOM_COUNTERS.may_load(deps.storage, &name)?.unwrap_or(0);
+  CUSTOM_COUNTERS.remove(deps.storage, &name);
+  
+  Ok(Response::new()
+      .add_attribute("action", "reset_counter")
+      .add_attribute("name", name)
+      .add_attribute("previous", previous.to_string()))
+}
+
+// Zero test_run_count, stored runs and messages are left in place (admin only)
+pub fn execute_reset_counters(
+  deps: DepsMut,
+  env: Env,
+  info: MessageInfo,
+) -> Result<Response, ContractError> {
+  let mut state = STATE.load(deps.storage)?;
+  
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  state.test_run_count = 0;
+  state.last_test_timestamp = Some(env.block.time.seconds());
+  STATE.save(deps.storage, &state)?;
+  
+  Ok(Response::new()
+      .add_attribute("action", "reset_counters"))
+}
+
+// Delete a bounded range of messages (admin only)
+pub fn execute_delete_range(
+  deps: DepsMut,
+  _env: Env,
+  info: MessageInfo,
+  start_after: Option<String>,
+  limit: u32,
+) -> Result<Response, ContractError> {
+  let state = STATE.load(deps.storage)?;
+  
+  // Only owner can delete messages
+  if !state.is_owner(&info.sender) {
+      return Err(ContractError::Unauthorized {});
+  }
+  
+  if limit > MAX_DELETE_RANGE {
+      return Err(ContractError::LimitExceeded { 
+          requested: limit as u64, 
+          max: MAX_DELETE_RANGE as u64 
+      });
+  }
+  
+  // Only the requested batch is read, the rest of the map is never touched
+  let start = start_after.as_deref().map(Bound::exclusive);
+  let batch: Vec<(String, StoredMessage)> = MESSAGES
+      .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+      .take(limit as usize)
+      .collect::<StdResult<Vec<_>>>()?;
+  
+  let keys: Vec<String> = batch.iter().map(|(key, _)| key.clone()).collect();
+  let bytes_freed = remove_messages(deps.storage, &keys)?;
+  
+  let last_key = batch.last().map(|(key, _)| key.clone()).unwrap_or_default();
+  
+  Ok(Response::new()
+      .add_attribute("action", "delete_range")
+      .add_attribute("removed", batch.len().to_string())
+      .add_attribute("bytes_freed", bytes_freed.to_string())
+      .add_attribute("last_key", last_key))
+}
+
+#[entry_point]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+  match msg {
+      QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
+      QueryMsg::GetMessage { id } => to_json_binary(&query_message(deps, id)?),
+      QueryMsg::ListMessages { start_after, end_before, limit, include_archived } => 
+          to_json_binary(&query_list_messages(deps, start_after, end_before, limit, include_archived.unwrap_or(false))?),
+      QueryMsg::GetTestRuns { start_after, limit, chain, order, sort, tag, node_version, include_proofs } => 
+          to_json_binary(&query_test_runs(deps, start_after, limit, chain.as_deref(), (order.as_deref(), sort.unwrap_or_default()), (tag.as_deref(), node_version.as_deref()), include_proofs.unwrap_or(false))?),
+      QueryMsg::GetTestRun { run_id, include_proofs, chain } => 
+          to_json_binary(&query_test_run(deps, run_id, include_proofs.unwrap_or(false), chain.as_deref())?),
+      QueryMsg::TopRunsByGas { limit } => to_json_binary(&query_top_runs_by_gas(deps, limit)?),
+      QueryMsg::GetRunSamples { run_id, start_after, limit, chain } => 
+          to_json_binary(&query_run_samples(deps, run_id, start_after, limit, chain.as_deref())?),
+      QueryMsg::GetDashboard { runs_limit } => to_json_binary(&query_dashboard(deps, runs_limit)?),
+      QueryMsg::GetGasModel { run_id } => to_json_binary(&query_gas_model(deps, run_id)?),
+      QueryMsg::EstimateGas { bytes, chain } => to_json_binary(&query_estimate_gas(deps, bytes, chain.as_deref())?),
+      QueryMsg::IsOwner { address } => to_json_binary(&query_is_owner(deps, address)?),
+      QueryMsg::EstimateFee { bytes, chain } => to_json_binary(&query_estimate_fee(deps, bytes, chain.as_deref())?),
+      QueryMsg::CompareRuns { base, against, chain } => to_json_binary(&query_compare_runs(deps, base, against, chain.as_deref())?),
+      QueryMsg::GetLatestDelta { chain } => to_json_binary(&query_latest_delta(deps, &chain)?),
+      QueryMsg::GetGasSummary { chain, window } => to_json_binary(&query_gas_summary(deps, chain.as_deref(), window)?),
+      QueryMsg::GetGasSummaryExact {} => to_json_binary(&query_gas_summary_exact(deps)?),
+      QueryMsg::ListChains {} => to_json_binary(&query_list_chains(deps)?),
+      QueryMsg::GetChainRuns { chain, start_after, limit } => 
+          to_json_binary(&query_test_runs(deps, start_after, limit, Some(&chain), (Some("asc"), RunSort::ById), (None, None), false)?),
+      QueryMsg::GetGasSummaryRelative { baseline_gas_per_byte } => 
+          to_json_binary(&query_gas_summary_relative(deps, baseline_gas_per_byte)?),
+      QueryMsg::GetGasSummaryByChain {} => to_json_binary(&query_gas_summary_by_chain(deps)?),
+      QueryMsg::GetGasCurve {} => to_json_binary(&query_gas_curve(deps)?),
+      QueryMsg::GetMessageBlockSpan {} => to_json_binary(&query_message_block_span(deps)?),
+      QueryMsg::GetGasVsCodeSize {} => to_json_binary(&query_gas_vs_code_size(deps)?),
+      QueryMsg::GetHealth {} => to_json_binary(&query_health(deps)?),
+      QueryMsg::Health {} => to_json_binary(&query_liveness(deps)?),
+      QueryMsg::GetLatestRun { chain } => to_json_binary(&query_latest_run(deps, chain.as_deref())?),
+      QueryMsg::GetRunBySeq { seq } => to_json_binary(&query_run_by_seq(deps, seq)?),
+      QueryMsg::GetRunsByTime { from, to, limit, ascending } => 
+          to_json_binary(&query_runs_by_time(deps, from, to, limit, ascending.unwrap_or(true))?),
+      QueryMsg::GetRunTimeBounds {} => to_json_binary(&query_run_time_bounds(deps)?),
+      QueryMsg::GetGasSeries { chain, from, to, limit, start_after } => 
+          to_json_binary(&query_gas_series(deps, chain.as_deref(), (from, to), limit, start_after)?),
+      QueryMsg::GetLengthHistogram { bucket_size, max_scan } => 
+          to_json_binary(&query_length_histogram(deps, bucket_size, max_scan)?),
+      QueryMsg::GetCounter { name } => to_json_binary(&query_counter(deps, name)?),
+      QueryMsg::GetVersion {} => to_json_binary(&query_version(deps)?),
+      QueryMsg::DecomposeRun { run_id, chain } => to_json_binary(&query_decompose_run(deps, run_id, chain.as_deref())?),
+      QueryMsg::GetStorageStats {} => to_json_binary(&query_storage_stats(deps)?),
+      QueryMsg::ListRecorders {} => to_json_binary(&query_recorders(deps)?),
+      QueryMsg::ValidateMessage { content } => to_json_binary(&query_validate_message(deps, content)?),
+      QueryMsg::ListMessagesSince { since, limit } => to_json_binary(&query_list_messages_since(deps, since, limit)?),
+      QueryMsg::ConcatMessages { separator, limit } => 
+          to_json_binary(&query_concat_messages(deps, separator.unwrap_or_default(), limit)?),
+      QueryMsg::ListMessagesByPrefix { prefix, limit } => 
+          to_json_binary(&query_list_messages_by_prefix(deps, prefix, limit)?),
+      QueryMsg::GetClearHistory {} => to_json_binary(&query_clear_history(deps)?),
+      QueryMsg::ExportRunsCsv { start_after, limit } => to_json_binary(&query_export_runs_csv(deps, start_after, limit)?),
+  }
+}
+
+// Query contract configuration
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+  let state = STATE.load(deps.storage)?;
+  let config = load_config(deps.storage)?;
+  
+  Ok(ConfigResponse {
+      owner: state.owner.to_string(),
+      test_count: state.test_run_count,
+      last_test: state.last_test_timestamp,
+      max_message_size: config.max_message_size,
+      max_list_limit: config.max_list_limit,
+      max_runs_limit: config.max_runs_limit,
+      max_messages: config.max_messages,
+      eviction: config.eviction,
+      hex_tx_proofs: config.hex_tx_proofs,
+      strict_validation: config.strict_validation,
+      regression_threshold_bps: config.regression_threshold_bps,
+      min_gas_per_byte: config.min_gas_per_byte,
+      max_gas_per_byte: config.max_gas_per_byte,
+      avg_gas_tolerance_bps: config.avg_gas_tolerance_bps,
+      open_recording: config.open_recording,
+      max_proof_bytes: config.max_proof_bytes,
+      gas_budget: config.gas_budget,
+      // Contracts that haven't written since the aggregates were added report 0 until they do
+      cumulative_gas: AGGREGATES.may_load(deps.storage)?.map_or(Uint128::zero(), |aggregates| aggregates.total_gas),
+      message_count: state.message_count,
+      paused: state.paused,
+      renounced: state.renounced,
+      created_at_height: state.created_at_height,
+      lifetime_store_count: state.lifetime_store_count,
+  })
+}
+
+// Query msg by id
+fn query_message(deps: Deps, id: String) -> StdResult<MessageResponse> {
+  let message = MESSAGES
+      .may_load(deps.storage, &id)?
+      .ok_or_else(|| StdError::generic_err(format!("message not found: {}", id)))?;
+  
+  message_response(id, message)
+}
+
+fn message_response(id: String, message: StoredMessage) -> StdResult<MessageResponse> {
+  Ok(MessageResponse {
+      content: message.decoded_content()?,
+      id,
+      length: message.length,
+      time: message.stored_at,
+      gas_used: message.gas_used,
+      archived: message.archived,
+  })
+}
+
+/// List msgs paginated
+fn query_list_messages(
+  deps: Deps,
+  start_after: Option<String>,
+  end_before: Option<String>,
+  limit: Option<u32>,
+  include_archived: bool,
+) -> StdResult<ListMessagesResponse> {
+  // Default limit is 10, max allowed comes from config
+  let config = load_config(deps.storage)?;
+  let limit = limit.unwrap_or(10).min(config.max_list_limit) as usize;
+  
+  if let (Some(start), Some(end)) = (&start_after, &end_before) {
+      if start >= end {
+          return Err(StdError::generic_err(format!(
+              "start_after ({}) must sort before end_before ({})",
+              start, end
+          )));
+      }
+  }
+  
+  // Convert start_after/end_before to exclusive Bounds
+  let start = start_after.as_deref().map(Bound::exclusive);
+  let end = end_before.as_deref().map(Bound::exclusive);
+
+  let messages: StdResult<Vec<_>> = MESSAGES
+      .range(deps.storage, start, end, cosmwasm_std::Order::Ascending)
+      .filter(|item| include_archived || item.as_ref().map_or(true, |(_, message)| !message.archived))
+      .take(limit)
+      .map(|item| {
+          let (id, message) = item?;
+          message_response(id, message)
+      })
+      .collect();
+  
+  let msgs = messages?;
+  
+  Ok(ListMessagesResponse {
+      msgs: msgs.clone(),
+      count: msgs.len() as u64,
+  })
+}
+
+/// List msgs stored strictly after `since` (seconds), ordered by id
+/// stored_at isn't part of the key, so this is a filtered scan over MESSAGES
+fn query_list_messages_since(deps: Deps, since: u64, limit: Option<u32>) -> StdResult<ListMessagesResponse> {
+  let config = load_config(deps.storage)?;
+  let limit = limit.unwrap_or(10).min(config.max_list_limit) as usize;
+  
+  let mut msgs = vec![];
+  for item in MESSAGES.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+      if msgs.len() >= limit {
+          break;
+      }
+      let (id, message) = item?;
+      if message.stored_at > since {
+          msgs.push(message_response(id, message)?);
+      }
+  }
+  
+  Ok(ListMessagesResponse {
+      count: msgs.len() as u64,
+      msgs,
+  })
+}
+
+// Ids sort lexicographically, so matches are a contiguous range starting at the prefix
+fn query_list_messages_by_prefix(deps: Deps, prefix: String, limit: Option<u32>) -> StdResult<ListMessagesResponse> {
+  if prefix.is_empty() {
+      return Err(StdError::generic_err("prefix cannot be empty"));
+  }
+  
+  let config = load_config(deps.storage)?;
+  let limit = limit.unwrap_or(10).min(config.max_list_limit) as usize;
+  
+  let msgs = MESSAGES
+      .range(deps.storage, Some(Bound::inclusive(prefix.as_str())), None, cosmwasm_std::Order::Ascending)
+      .take_while(|item| item.as_ref().map_or(true, |(id, _)| id.starts_with(&prefix)))
+      .take(limit)
+      .map(|item| {
+          let (id, message) = item?;
+          message_response(id, message)
+      })
+      .collect::<StdResult<Vec<_>>>()?;
+  
+  Ok(ListMessagesResponse {
+      count: msgs.len() as u64,
+      msgs,
+  })
+}
+
+// start_after is exclusive in the direction of travel, a max bound when descending
+fn range_bounds<T>(after: Option<T>, order: cosmwasm_std::Order) -> (Option<T>, Option<T>) {
+  match order {
+      cosmwasm_std::Order::Ascending => (after, None),
+      cosmwasm_std::Order::Descending => (None, after),
+  }
+}
+
+/// Query prev runs paginated
+fn query_test_runs(
+  deps: Deps,
+  start_after: Option<String>,
+  limit: Option<u32>,
+  chain: Option<&str>,
+  (order, sort): (Option<&str>, RunSort),
+  (tag, node_version): (Option<&str>, Option<&str>),
+  include_proofs: bool,
+) -> StdResult<TestRunsResponse> {
+  // Proof lists can be long, pages carrying them stay at the default size
+  if include_proofs && limit.map_or(false, |limit| limit > MAX_PROOF_PAGE_LIMIT) {
+      return Err(StdError::generic_err(format!(
+          "limit can't exceed {} with include_proofs", MAX_PROOF_PAGE_LIMIT
+      )));
+  }
+  
+  // Default limit is 5, max allowed comes from config
+  let config = load_config(deps.storage)?;
+  let limit = limit.unwrap_or(5).min(config.max_runs_limit) as usize;
+  
+  let order = match order.unwrap_or("desc") {
+      "asc" => cosmwasm_std::Order::Ascending,
+      "desc" => cosmwasm_std::Order::Descending,
+      other => return Err(StdError::generic_err(format!("order must be \"asc\" or \"desc\", got \"{}\"", other))),
+  };
+
+  let candidates: Box<dyn Iterator<Item = StdResult<(String, TestRunStats)>>> = match (sort, chain) {
+      // Seqs are never reassigned, so pages stay stable while runs are recorded
+      (RunSort::BySeq, chain) => {
+          let after = start_after
+              .map(|seq| seq.parse::<u64>().map(Bound::exclusive))
+              .transpose()
+              .map_err(|_| StdError::generic_err("start_after must be a run seq with sort by_seq"))?;
+          let (start, end) = range_bounds(after, order);
+          let chain = chain.map(normalize_chain_id);
+          Box::new(
+              RUN_SEQS
+                  .range(deps.storage, start, end, order)
+                  .filter(move |item| item.as_ref().map_or(true, |(_, (run_chain, _))| chain.as_ref().map_or(true, |chain| chain == run_chain)))
+                  .map(|item| {
+                      let (_, (chain, run_id)) = item?;
+                      TEST_RUNS.load(deps.storage, (&chain, &run_id)).map(|run| (run_id, run))
+                  }),
+          )
+      }
+      // Ordered by run id, an id recorded on several chains yields each of its runs
+      (RunSort::ById, None) => {
+          let (start, end) = range_bounds(start_after.as_deref().map(Bound::exclusive), order);
+          Box::new(
+          RUN_CHAINS
+              .range(deps.storage, start, end, order)
+              .flat_map(|item| match item {
+                  Ok((run_id, chains)) => chains
+                      .into_iter()
+                      .map(|chain| TEST_RUNS.load(deps.storage, (&chain, &run_id)).map(|run| (run_id.clone(), run)))
+                      .collect(),
+                  Err(err) => vec![Err(err)],
+              }),
+          )
+      }
+      (RunSort::ById, Some(chain)) => {
+          let (start, end) = range_bounds(start_after.as_deref().map(Bound::exclusive), order);
+          TEST_RUNS
+              .prefix(&normalize_chain_id(chain))
+              .range(deps.storage, start, end, order)
+      }
+  };
+  
+  // Tags and versions aren't indexed, matching runs are found by scanning
+  let matching = candidates.filter(|item| {
+      item.as_ref().map_or(true, |(_, run)| {
+          tag.map_or(true, |tag| run.tags.iter().any(|t| t == tag))
+              && node_version.map_or(true, |version| run.node_version.as_deref() == Some(version))
+      })
+  });
+  
+  // By id, start_after skips every run under an id, so a full page still takes the rest of its last id
+  let mut runs: Vec<TestRunResponse> = vec![];
+  for item in matching {
+      let (id, run) = item?;
+      if runs.len() >= limit && (sort == RunSort::BySeq || runs.last().map_or(true, |last| last.id != id)) {
+          break;
+      }
+      let tx_proofs = include_proofs.then(|| run.tx_proofs.clone());
+      runs.push(TestRunResponse {
+          tx_proofs,
+          ..test_run_response(id, run)
+      });
+  }
+  
+  // A short page means there is nothing left
+  let next_start_after = match (runs.last(), sort) {
+      (Some(last), RunSort::ById) if runs.len() >= limit => Some(last.id.clone()),
+      (Some(last), RunSort::BySeq) if runs.len() >= limit => Some(last.seq.to_string()),
+      _ => None,
+  };
+  
+  Ok(TestRunsResponse { runs, next_start_after })
+}
+
+// Bundle the queries a front-end makes on load
+fn query_dashboard(deps: Deps, runs_limit: Option<u32>) -> StdResult<DashboardResponse> {
+  Ok(DashboardResponse {
+      config: query_config(deps)?,
+      summary: query_gas_summary(deps, None, None)?,
+      runs: query_test_runs(deps, None, runs_limit, None, (None, RunSort::ById), (None, None), false)?.runs,
+  })
+}
+
+// Check an address against the owner, always false once renounced
+fn query_is_owner(deps: Deps, address: String) -> StdResult<IsOwnerResponse> {
+  let address = deps.api.addr_validate(&address)?;
+  let state = STATE.load(deps.storage)?;
+  
+  Ok(IsOwnerResponse { is_owner: state.is_owner(&address) })
+}
+
+// Runs are keyed by id, so every run is loaded and sorted by gas
+fn query_top_runs_by_gas(deps: Deps, limit: Option<u32>) -> StdResult<TestRunsResponse> {
+  let config = load_config(deps.storage)?;
+  let limit = limit.unwrap_or(5).min(config.max_runs_limit) as usize;
+  
+  let mut runs = TEST_RUNS
+      .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .collect::<StdResult<Vec<_>>>()?;
+  // Stable sort keeps run_id order between equal totals
+  runs.sort_by(|(_, a), (_, b)| b.total_gas.cmp(&a.total_gas));
+  
+  let runs = runs
+      .into_iter()
+      .take(limit)
+      .map(|((_, id), run)| test_run_response(id, run))
+      .collect();
+  
+  Ok(TestRunsResponse { runs, next_start_after: None })
+}
+
+// Query a single run by id
+fn query_test_run(deps: Deps, run_id: String, include_proofs: bool, chain: Option<&str>) -> StdResult<TestRunResponse> {
+  let (_, run) = find_run(deps.storage, &run_id, chain)?
+      .ok_or_else(|| StdError::not_found(format!("test run {}", run_id)))?;
+  
+  let tx_proofs = include_proofs.then(|| run.tx_proofs.clone());
+  Ok(TestRunResponse {
+      tx_proofs,
+      ..test_run_response(run_id, run)
+  })
+}
+
+// Page through a run's samples in submission order
+fn query_run_samples(
+  deps: Deps,
+  run_id: String,
+  start_after: Option<u32>,
+  limit: Option<u32>,
+  chain: Option<&str>,
+) -> StdResult<RunSamplesResponse> {
+  let (_, run) = find_run(deps.storage, &run_id, chain)?
+      .ok_or_else(|| StdError::generic_err(format!("test run not found: {}", run_id)))?;
+  
+  let config = load_config(deps.storage)?;
+  let limit = limit.unwrap_or(config.max_list_limit).min(MAX_GAS_SAMPLES as u32) as usize;
+  let skip = start_after.map_or(0, |index| index as usize + 1);
+  
+  let samples = run.samples.into_iter().skip(skip).take(limit).collect();
+  
+  Ok(RunSamplesResponse { samples })
+}
+
+fn test_run_response(id: String, run: TestRunStats) -> TestRunResponse {
+  let duration_secs = run.duration_secs();
+  TestRunResponse {
+      id,
+      time: run.timestamp,
+      count: run.message_count,
+      gas: run.total_gas,
+      avg_gas: run.avg_gas_per_byte,
+      chain: run.chain_id,
+      tx_count: run.tx_proofs.len() as u32,
+      code_size: run.code_size_bytes,
+      bytes: run.total_bytes,
+      min_gas: run.min_gas,
+      max_gas: run.max_gas,
+      median_gas: run.median_gas,
+      tx_proofs: None,
+      duration_secs,
+      msgs_per_sec: duration_secs.and_then(|duration| run.message_count.checked_div(duration)),
+      tags: run.tags,
+      note: run.note,
+      seq: run.seq,
+      started_at: run.started_at,
+      finished_at: run.finished_at,
+      node_version: run.node_version,
+      contract_version: run.contract_version,
+      coverage: run.coverage,
+  }
+}
+
+// Prefer the reported byte count, estimating from average gas per byte otherwise
+fn run_bytes(run: &TestRunStats) -> u64 {
+  if run.total_bytes > 0 {
+      run.total_bytes
+  } else if !run.avg_gas_per_byte.is_zero() {
+      // The quotient is computed in u128, clamped rather than truncated into u64
+      u64::try_from(run.total_gas.u128() / run.avg_gas_per_byte.u128()).unwrap_or(u64::MAX)
+  } else {
+      0
+  }
+}
+
+/// Distinct chain ids, sorted
+/// Scans every run, fine for a dropdown but grows with the run history
+fn query_list_chains(deps: Deps) -> StdResult<ChainsResponse> {
+  let mut chains = BTreeSet::new();
+  for item in TEST_RUNS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+      chains.insert(item?.1.chain_id);
+  }
+  
+  Ok(ChainsResponse { chains: chains.into_iter().collect() })
+}
+
+/// Per-chain totals in one pass over the runs, ordered by chain id
+fn query_gas_summary_by_chain(deps: Deps) -> StdResult<ChainGasSummaryResponse> {
+  let mut chains: BTreeMap<String, (ChainGasSummary, u64)> = BTreeMap::new();
+  for item in TEST_RUNS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+      let (_, run) = item?;
+      let chain_id = normalize_chain_id(&run.chain_id);
+      let (summary, bytes) = chains.entry(chain_id.clone()).or_insert_with(|| (
+          ChainGasSummary {
+              chain_id,
+              run_count: 0,
+              msg_count: 0,
+              total_gas: Uint128::zero(),
+              gas_per_byte: Uint128::zero(),
+          },
+          0,
+      ));
+      let overflow = || StdError::generic_err(format!("gas summary overflow: totals for chain {} are out of range", summary.chain_id));
+      summary.run_count += 1;
+      summary.msg_count = summary.msg_count.checked_add(run.message_count).ok_or_else(overflow)?;
+      summary.total_gas = summary.total_gas.checked_add(run.total_gas).map_err(|_| overflow())?;
+      *bytes = bytes.checked_add(run_bytes(&run)).ok_or_else(overflow)?;
+  }
+  
+  let chains = chains
+      .into_values()
+      .map(|(mut summary, bytes)| {
+          if bytes > 0 {
+              summary.gas_per_byte = Uint128::new(summary.total_gas.u128() / bytes as u128);
+          }
+          summary
+      })
+      .collect();
+  
+  Ok(ChainGasSummaryResponse { chains })
+}
+
+/// Gas summary over every run, for contracts embedding this one directly
+/// Callers on chain should send `QueryMsg::GetGasSummary` as a smart query instead
+pub fn query_gas_summary_raw(deps: Deps) -> StdResult<GasSummary> {
+  query_gas_summary(deps, None, None)
+}
+
+/// Query gas usage metrics
+/// The spread fields are only filled for a `window`, see gas_per_byte_spread
+fn query_gas_summary(deps: Deps, chain: Option<&str>, window: Option<u32>) -> StdResult<GasSummary> {
+  // Filtered summaries and contracts without aggregates yet need the full scan
+  let summary = match (chain, AGGREGATES.may_load(deps.storage)?) {
+      (None, Some(aggregates)) => cached_gas_summary(deps, &aggregates)?,
+      _ => scan_gas_summary(deps, chain)?.0,
+  };
+  
+  match window {
+      Some(window) => gas_per_byte_spread(summary, windowed_gas_per_byte(deps, chain, window)?),
+      None => Ok(summary),
+  }
+}
+
+/// Gas summary from the cached aggregates and counting indexes, without the spread
+fn cached_gas_summary(deps: Deps, aggregates: &Aggregates) -> StdResult<GasSummary> {  
+  let first_key = |index: &Map<'static, u128, u64>, order| -> StdResult<Option<Uint128>> {
+      Ok(index.keys(deps.storage, None, None, order).next().transpose()?.map(Uint128::new))
+  };
+  let min_gas = first_key(&RUN_MIN_GAS, cosmwasm_std::Order::Ascending)?;
+  let max_gas = first_key(&RUN_MAX_GAS, cosmwasm_std::Order::Descending)?;
+  
+  let min_msg_length = MESSAGE_LENGTHS
+      .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .next()
+      .transpose()?
+      .unwrap_or(0);
+  let max_msg_length = MESSAGE_LENGTHS
+      .keys(deps.storage, None, None, cosmwasm_std::Order::Descending)
+      .next()
+      .transpose()?
+      .unwrap_or(0);
+  
+  build_gas_summary(aggregates, min_gas, max_gas, (min_msg_length, max_msg_length))
+}
+
+/// Gas summary with gas_per_byte expressed relative to a baseline
+fn query_gas_summary_relative(deps: Deps, baseline_gas_per_byte: Uint128) -> StdResult<RelativeGasSummary> {
+  if baseline_gas_per_byte.is_zero() {
+      return Err(StdError::generic_err("baseline_gas_per_byte must be greater than zero"));
+  }
+  
+  let summary = query_gas_summary(deps, None, None)?;
+  let ratio_bps = summary
+      .gas_per_byte
+      .checked_multiply_ratio(10_000u128, baseline_gas_per_byte)
+      .map_err(|err| StdError::generic_err(err.to_string()))?;
+  
+  Ok(RelativeGasSummary { summary, baseline_gas_per_byte, ratio_bps })
+}
+
+/// Recompute the gas summary, spread included, from every stored run and message
+fn query_gas_summary_exact(deps: Deps) -> StdResult<GasSummary> {
+  let (summary, gas_per_byte) = scan_gas_summary(deps, None)?;
+  gas_per_byte_spread(summary, gas_per_byte)
+}
+
+/// Gas summary recomputed from every stored run and message, without the spread,
+/// along with each matching run's avg_gas_per_byte
+fn scan_gas_summary(deps: Deps, chain: Option<&str>) -> StdResult<(GasSummary, Vec<Uint128>)> {
+  let runs: StdResult<Vec<TestRunStats>> = TEST_RUNS
+      .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .map(|item| item.map(|(_, run)| run))
+      .filter(|run| match run {
+          Ok(run) => on_chain(run, chain),
+          Err(_) => true,
+      })
+      .collect();
+  let runs = runs?;
+  
+  // Range of stored payload sizes, independent of the chain filter
+  let mut length_range: Option<(u64, u64)> = None;
+  for item in MESSAGES.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+      let length = item?.1.length;
+      length_range = Some(length_range.map_or((length, length), |(min, max)| (min.min(length), max.max(length))));
+  }
+  
+  let mut aggregates = Aggregates::default();
+  for run in &runs {
+      aggregates.add(run)?;
+  }
+  let min_gas = runs.iter().filter_map(|run| run.min_gas).min();
+  let max_gas = runs.iter().filter_map(|run| run.max_gas).max();
+  
+  let summary = build_gas_summary(&aggregates, min_gas, max_gas, length_range.unwrap_or((0, 0)))?;
+  Ok((summary, runs.iter().map(|run| run.avg_gas_per_byte).collect()))
+}
+
+/// avg_gas_per_byte of the newest `window` runs in recording order, capped at max_list_limit
+/// Runs recorded before seqs were assigned have none and are left out
+fn windowed_gas_per_byte(deps: Deps, chain: Option<&str>, window: u32) -> StdResult<Vec<Uint128>> {
+  if window == 0 {
+      return Err(StdError::generic_err("window must be greater than zero"));
+  }
+  let window = window.min(load_config(deps.storage)?.max_list_limit) as usize;
+  
+  let chain = chain.map(normalize_chain_id);
+  RUN_SEQS
+      .range(deps.storage, None, None, cosmwasm_std::Order::Descending)
+      .filter(|item| item.as_ref().map_or(true, |(_, (run_chain, _))| chain.as_ref().map_or(true, |chain| chain == run_chain)))
+      .take(window)
+      .map(|item| {
+          let (_, (chain, run_id)) = item?;
+          Ok(TEST_RUNS.load(deps.storage, (&chain, &run_id))?.avg_gas_per_byte)
+      })
+      .collect()
+}
+
+/// Fill in the std dev and percentiles of the given per-run avg_gas_per_byte values
+/// Percentiles use the nearest rank, the value at position ceil(p * n / 100) in sorted
+/// order, so each is an observed run value; the std dev is the floor of the population std dev
+fn gas_per_byte_spread(mut summary: GasSummary, mut values: Vec<Uint128>) -> StdResult<GasSummary> {
+  if values.len() < 2 {
+      return Ok(summary);
+  }
+  
+  values.sort();
+  let n = values.len();
+  let percentile = |p: usize| values[(p * n).div_ceil(100) - 1];
+  let sum = values.iter().try_fold(Uint128::zero(), |sum, value| sum.checked_add(*value))?;
+  let squares = values
+      .iter()
+      .try_fold(Uint256::zero(), |squares, value| squares.checked_add(Uint256::from(*value) * Uint256::from(*value)))?;
+  
+  summary.stddev_gas_per_byte = Some(std_dev(n as u64, sum, squares));
+  summary.p50 = Some(percentile(50));
+  summary.p90 = Some(percentile(90));
+  summary.p99 = Some(percentile(99));
+  Ok(summary)
+}
+
+fn build_gas_summary(
+  aggregates: &Aggregates,
+  min_gas: Option<Uint128>,
+  max_gas: Option<Uint128>,
+  (min_msg_length, max_msg_length): (u64, u64),
+) -> StdResult<GasSummary> {
+  let total_messages = aggregates.total_messages;
+  let total_gas = aggregates.total_gas;
+  let total_bytes = aggregates.total_bytes.u128();
+  
+  // Calculate averages (safely handle division by zero)
+  // Summed gas over summed messages, so each run counts in proportion to its message_count
+  let avg_gas = if total_messages > 0 {
+      Uint128::new(total_gas.u128() / total_messages as u128)
+  } else {
+      Uint128::zero()
+  };
+  
+  // Summed gas over summed bytes weights each run by its bytes, reported bytes are exact
+  // and only runs without them fall back to the estimate from their avg_gas
+  let gas_per_byte = if total_bytes > 0 {
+      Uint128::new(total_gas.u128() / total_bytes)
+  } else {
+      Uint128::zero()
+  };
+  
+  // Each run's own average weighted by its bytes, at most the largest per-run average
+  let weighted_gas_per_byte = if total_bytes > 0 {
+      Uint128::try_from(aggregates.weighted_gas / Uint256::from(total_bytes))
+          .map_err(|_| StdError::generic_err("gas summary overflow: weighted_gas_per_byte is out of range"))?
+  } else {
+      Uint128::zero()
+  };
+  
+  let avg_bytes_per_msg = if total_messages > 0 {
+      u64::try_from(total_bytes / total_messages as u128).unwrap_or(u64::MAX)
+  } else {
+      0
+  };
+  
+  let gas_std_dev = std_dev(aggregates.run_count, aggregates.total_gas, aggregates.gas_squares);
+  
+  let avg_msgs_per_sec = aggregates.timed_messages.checked_div(aggregates.timed_secs);
+  
+  Ok(GasSummary {
+      msg_count: total_messages,
+      total_gas,
+      avg_gas,
+      total_bytes: u64::try_from(total_bytes).unwrap_or(u64::MAX),
+      gas_per_byte,
+      avg_bytes_per_msg,
+      gas_std_dev,
+      weighted_gas_per_byte,
+      min_gas,
+      max_gas,
+      min_msg_length,
+      max_msg_length,
+      avg_msgs_per_sec,
+      runs_excluded_from_bytes: aggregates.runs_without_bytes,
+      estimated: aggregates.runs_estimated_bytes > 0,
+      stddev_gas_per_byte: None,
+      p50: None,
+      p90: None,
+      p99: None,
+  })
+}
+
+/// Population standard deviation from the count, sum and sum of squares,
+/// zero for fewer than two values
+fn std_dev(n: u64, sum: Uint128, squares: Uint256) -> Uint128 {
+  if n < 2 {
+      return Uint128::zero();
+  }
+  
+  // Var = (n * sum(x^2) - sum(x)^2) / n^2, in 256 bits so large gas totals don't saturate
+  let n = Uint256::from(n);
+  let sum = Uint256::from(sum);
+  let spread = n.saturating_mul(squares).saturating_sub(sum * sum);
+  // The std dev never exceeds the largest value, so it fits back into 128 bits
+  Uint128::try_from((spread / n / n).isqrt()).unwrap_or(Uint128::MAX)
+}
+
+/// Query gas per byte by message length
+fn query_gas_curve(deps: Deps) -> StdResult<GasCurveResponse> {
+  // Group runs by measured length, BTreeMap keeps lengths sorted ascending
+  let mut by_length: BTreeMap<u64, (Uint128, u64)> = BTreeMap::new();
+  
+  for item in TEST_RUNS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+      let (_, run) = item?;
+      if let Some(length) = run.byte_length {
+          let entry = by_length.entry(length).or_insert((Uint128::zero(), 0));
+          entry.0 = entry.0.checked_add(run.avg_gas_per_byte).map_err(StdError::overflow)?;
+          entry.1 += 1;
+      }
+  }
+  
+  let points = by_length
+      .into_iter()
+      .map(|(length, (gas_sum, runs))| GasCurvePoint {
+          length,
+          avg_gas_per_byte: Uint128::new(gas_sum.u128() / runs as u128),
+          runs,
+      })
+      .collect();
+  
+  Ok(GasCurveResponse { points })
+}
+
+/// Query the block height range covered by stored messages
+fn query_message_block_span(deps: Deps) -> StdResult<MessageBlockSpanResponse> {
+  let mut bounds: Option<(u64, u64)> = None;
+  
+  for item in MESSAGES.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+      let (_, message) = item?;
+      // Skip messages stored before heights were tracked
+      if message.stored_at_height == 0 {
+          continue;
+      }
+      let height = message.stored_at_height;
+      bounds = Some(match bounds {
+          Some((first, last)) => (first.min(height), last.max(height)),
+          None => (height, height),
+      });
+  }
+  
+  let (first_height, last_height) = bounds.unwrap_or((0, 0));
+  
+  Ok(MessageBlockSpanResponse {
+      first_height,
+      last_height,
+      block_span: last_height - first_height,
+  })
+}
+
+/// Query total gas against code size for runs that reported one
+fn query_gas_vs_code_size(deps: Deps) -> StdResult<GasVsCodeSizeResponse> {
+  let mut points = vec![];
+  
+  for item in TEST_RUNS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+      let (_, run) = item?;
+      if let Some(code_size) = run.code_size_bytes {
+          points.push(CodeSizePoint {
+              code_size,
+              total_gas: run.total_gas,
+          });
+      }
+  }
+  
+  // Order by code size for plotting
+  points.sort_by_key(|point| point.code_size);
+  
+  Ok(GasVsCodeSizeResponse { points })
+}
+
+/// Query overall contract health
+fn query_health(deps: Deps) -> StdResult<HealthResponse> {
+  let state = STATE.load(deps.storage)?;
+  
+  if state.paused {
+      return Ok(HealthResponse {
+          status: HealthStatus::Frozen,
+          reason: Some("contract is paused".to_string()),
+      });
+  }
+  
+  if state.renounced {
+      return Ok(HealthResponse {
+          status: HealthStatus::Archived,
+          reason: Some("ownership renounced".to_string()),
+      });
+  }
+  
+  // The cached run count can't exceed the stored runs, it may be lower after ResetCounters.
+  // The aggregates track the stored runs, so this stays a constant number of reads;
+  // contracts without aggregates yet get them on the next write and skip the check
+  let stored_runs = AGGREGATES.may_load(deps.storage)?.map(|aggregates| aggregates.run_count);
+  
+  if let Some(stored_runs) = stored_runs.filter(|stored_runs| state.test_run_count > *stored_runs) {
+      return Ok(HealthResponse {
+          status: HealthStatus::Degraded,
+          reason: Some(format!(
+              "test_run_count is {} but {} runs are stored",
+              state.test_run_count, stored_runs
+          )),
+      });
+  }
+  
+  Ok(HealthResponse {
+      status: HealthStatus::Healthy,
+      reason: None,
+  })
+}
+
+/// Newest run by timestamp, read from the per-chain pointers kept at record time
+/// One load for a chain, one per recorded chain otherwise, never a scan of the runs
+fn query_latest_run(deps: Deps, chain: Option<&str>) -> StdResult<TestRunResponse> {
+  let pointers = match chain {
+      Some(chain) => {
+          let chain = normalize_chain_id(chain);
+          LATEST_RUNS
+              .may_load(deps.storage, &chain)?
+              .map(|run_id| (chain, run_id))
+              .into_iter()
+              .collect()
+      }
+      None => LATEST_RUNS
+          .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+          .collect::<StdResult<Vec<_>>>()?,
+  };
+  
+  let mut latest: Option<(String, TestRunStats)> = None;
+  for (chain, run_id) in pointers {
+      let run = TEST_RUNS.load(deps.storage, (&chain, &run_id))?;
+      let newer = latest
+          .as_ref()
+          .map_or(true, |(id, current)| (run.timestamp, &run_id) > (current.timestamp, id));
+      if newer {
+          latest = Some((run_id, run));
+      }
+  }
+  
+  let (run_id, run) = latest.ok_or_else(|| StdError::generic_err(ContractError::NoData {}.to_string()))?;
+  Ok(test_run_response(run_id, run))
+}
+
+// Resolve a sequence number through RUN_SEQS
+fn query_run_by_seq(deps: Deps, seq: u64) -> StdResult<TestRunResponse> {
+  let (chain, run_id) = RUN_SEQS
+      .may_load(deps.storage, seq)?
+      .ok_or_else(|| StdError::not_found(format!("test run seq {}", seq)))?;
+  let run = TEST_RUNS.load(deps.storage, (&chain, &run_id))?;
+  
+  Ok(test_run_response(run_id, run))
+}
+
+/// Walk the time index between two timestamps, both inclusive
+/// Runs recorded in the same second come back in chain then run_id order
+fn query_runs_by_time(
+  deps: Deps,
+  from: Option<u64>,
+  to: Option<u64>,
+  limit: Option<u32>,
+  ascending: bool,
+) -> StdResult<TestRunsResponse> {
+  let config = load_config(deps.storage)?;
+  let limit = limit.unwrap_or(config.max_runs_limit).min(config.max_runs_limit) as usize;
+  let order = if ascending { cosmwasm_std::Order::Ascending } else { cosmwasm_std::Order::Descending };
+  
+  // Empty strings sort before any chain, so these bounds cover whole seconds
+  let start = from.map(|from| Bound::inclusive((from, "", "")));
+  let end = to.and_then(|to| to.checked_add(1)).map(|to| Bound::exclusive((to, "", "")));
+  
+  let runs = RUN_TIMES
+      .keys(deps.storage, start, end, order)
+      .take(limit)
+      .map(|key| {
+          let (_, chain, run_id) = key?;
+          let run = TEST_RUNS.load(deps.storage, (&chain, &run_id))?;
+          Ok(test_run_response(run_id, run))
+      })
+      .collect::<StdResult<Vec<_>>>()?;
+  
+  Ok(TestRunsResponse { runs, next_start_after: None })
+}
+
+// Both ends of the time index, the count scans the run keys since ResetCounters
+// zeroes the state's test_run_count without deleting runs
+fn query_run_time_bounds(deps: Deps) -> StdResult<RunTimeBoundsResponse> {
+  let first = |order| -> StdResult<Option<u64>> {
+      Ok(RUN_TIMES.keys(deps.storage, None, None, order).next().transpose()?.map(|(time, _, _)| time))
+  };
+  let oldest = first(cosmwasm_std::Order::Ascending)?;
+  let newest = first(cosmwasm_std::Order::Descending)?;
+  // Contracts without aggregates yet fall back to counting the runs
+  let run_count = match AGGREGATES.may_load(deps.storage)? {
+      Some(aggregates) => aggregates.run_count,
+      None => TEST_RUNS.keys(deps.storage, None, None, cosmwasm_std::Order::Ascending).count() as u64,
+  };
+  
+  Ok(RunTimeBoundsResponse { oldest, newest, run_count })
+}
+
+/// Walk the time index for chart points, both time bounds inclusive
+/// Skipped runs don't count toward the limit, but every visited key counts toward
+/// MAX_SERIES_SCAN, and a page that hits it ends with `next` at the last key visited
+fn query_gas_series(
+  deps: Deps,
+  chain: Option<&str>,
+  (from, to): (Option<u64>, Option<u64>),
+  limit: Option<u32>,
+  start_after: Option<SeriesCursor>,
+) -> StdResult<GasSeriesResponse> {
+  let limit = limit.unwrap_or(DEFAULT_SERIES_LIMIT).min(MAX_SERIES_LIMIT) as usize;
+  let chain = chain.map(normalize_chain_id);
+  
+  // The cursor, when given, takes over from `from`
+  let start = match &start_after {
+      Some(cursor) => Some(Bound::exclusive((cursor.time, cursor.chain.as_str(), cursor.run_id.as_str()))),
+      None => from.map(|from| Bound::inclusive((from, "", ""))),
+  };
+  let end = to.and_then(|to| to.checked_add(1)).map(|to| Bound::exclusive((to, "", "")));
+  
+  let mut points = vec![];
+  let mut next = None;
+  for (scanned, key) in RUN_TIMES.keys(deps.storage, start, end, cosmwasm_std::Order::Ascending).enumerate() {
+      let (time, run_chain, run_id) = key?;
+      let on_series = chain.as_ref().map_or(true, |chain| *chain == run_chain);
+      if on_series {
+          let run = TEST_RUNS.load(deps.storage, (&run_chain, &run_id))?;
+          if !run.avg_gas_per_byte.is_zero() {
+              points.push(GasPoint { time, gas_per_byte: run.avg_gas_per_byte, chain: run_chain.clone() });
+          }
+      }
+      
+      if points.len() >= limit || scanned + 1 >= MAX_SERIES_SCAN {
+          next = Some(SeriesCursor { time, chain: run_chain, run_id });
+          break;
+      }
+  }
+  
+  Ok(GasSeriesResponse { points, next })
+}
+
+/// Bin stored messages by length, a message of length l falls in the bin starting at
+/// l - l % bucket_size, so a length on a bin edge opens the next bin. Bins stop at the
+/// configured max_message_size, messages stored before it was lowered land in the last bin
+fn query_length_histogram(deps: Deps, bucket_size: u64, max_scan: Option<u32>) -> StdResult<LengthHistogramResponse> {
+  let max_message_size = load_config(deps.storage)?.max_message_size;
+  if bucket_size == 0 || bucket_size > max_message_size {
+      return Err(StdError::generic_err(format!(
+          "bucket_size must be between 1 and {}", max_message_size
+      )));
+  }
+  
+  let mut bins: BTreeMap<u64, (u64, u64)> = BTreeMap::new();
+  let mut scanned = 0u64;
+  for item in MESSAGES
+      .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .take(max_scan.map_or(usize::MAX, |max_scan| max_scan as usize))
+  {
+      let length = item?.1.length;
+      let (count, total_bytes) = bins.entry(length.min(max_message_size) / bucket_size).or_default();
+      *count += 1;
+      *total_bytes = total_bytes.saturating_add(length);
+      scanned += 1;
+  }
+  
+  let buckets = bins
+      .into_iter()
+      .map(|(bin, (count, total_bytes))| LengthBucket {
+          start: bin * bucket_size,
+          end: (bin + 1).saturating_mul(bucket_size),
+          count,
+          total_bytes,
+      })
+      .collect();
+  
+  Ok(LengthHistogramResponse { buckets, scanned })
+}
+
+// Cheapest probe for monitoring, a single read of the state
+fn query_liveness(deps: Deps) -> StdResult<LivenessResponse> {
+  Ok(match STATE.may_load(deps.storage)? {
+      Some(state) => LivenessResponse {
+          ok: true,
+          paused: state.paused,
+          message_count: state.message_count,
+      },
+      None => LivenessResponse { ok: false, paused: false, message_count: 0 },
+  })
+}
+
+// Query a named counter, missing counters read as zero
+fn query_counter(deps: Deps, name: String) -> StdResult<CounterResponse> {
+  let value = CUSTOM_COUNTERS.may_load(deps.storage, &name)?.unwrap_or(0);
+  
+  Ok(CounterResponse { name, value })
+}
+
+// Query the cw2 contract version
+fn query_version(deps: Deps) -> StdResult<VersionResponse> {
+  let info = get_contract_version(deps.storage).map_err(|_| {
+      StdError::generic_err("contract version not set (instantiated before versioning)")
+  })?;
+  
+  Ok(VersionResponse {
+      contract: info.contract,
+      version: info.version,
+  })
+}
+
+// Query recent clear operations, oldest first
+fn query_clear_history(deps: Deps) -> StdResult<ClearHistoryResponse> {
+  let events = CLEAR_HISTORY.iter(deps.storage)?.collect::<StdResult<Vec<_>>>()?;
+  
+  Ok(ClearHistoryResponse { events })
+}
+
+// Export one GetTestRuns page as CSV, one line per run in ascending run_id order
+fn query_export_runs_csv(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<ExportRunsCsvResponse> {
+  // Pages like GetTestRuns in ascending run id order, so its cursor and limit caps apply
+  let page = query_test_runs(deps, start_after, limit, None, (Some("asc"), RunSort::ById), (None, None), false)?;
+  
+  let mut csv = String::from("run_id,timestamp,chain,count,total_gas,avg_gas_per_byte,tx_count\n");
+  for run in page.runs {
+      csv.push_str(&format!(
+          "{},{},{},{},{},{},{}\n",
+          csv_field(&run.id),
+          run.time,
+          csv_field(&run.chain),
+          run.count,
+          run.gas,
+          run.avg_gas,
+          run.tx_count,
+      ));
+  }
+  
+  Ok(ExportRunsCsvResponse { csv, next_start_after: page.next_start_after })
+}
+
+// Join decoded message contents in id order, archived messages included
+fn query_concat_messages(deps: Deps, separator: String, limit: Option<u32>) -> StdResult<ConcatMessagesResponse> {
+  let config = load_config(deps.storage)?;
+  let limit = limit.unwrap_or(10).min(config.max_list_limit) as usize;
+  
+  let contents = MESSAGES
+      .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .take(limit)
+      .map(|item| item?.1.decoded_content())
+      .collect::<StdResult<Vec<_>>>()?;
+  
+  Ok(ConcatMessagesResponse {
+      count: contents.len() as u64,
+      content: contents.join(&separator),
+  })
+}
+
+// Quote a CSV field when it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+  if value.contains([',', '"', '\n', '\r']) {
+      format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+      value.to_string()
+  }
+}
+
+// Dry-run the StoreMessage checks without writing
+fn query_validate_message(deps: Deps, content: String) -> StdResult<ValidateMessageResponse> {
+  let config = load_config(deps.storage)?;
+  let length = content.len() as u64;
+  
+  let reason = if length > config.max_message_size {
+      Some(ContractError::MessageTooLarge { 
+          size: length, 
+          max: config.max_message_size 
+      }.to_string())
+  } else {
+      None
+  };
+  
+  Ok(ValidateMessageResponse {
+      valid: reason.is_none(),
+      length,
+      reason,
+  })
+}
+
+// Query addresses allowed to record test runs
+fn query_recorders(deps: Deps) -> StdResult<RecordersResponse> {
+  let state = STATE.load(deps.storage)?;
+  
+  Ok(RecordersResponse {
+      recorders: state.recorders.into_iter().map(|addr| addr.to_string()).collect(),
+  })
+}
+
+/// Query the contract's storage footprint
+/// Scans every stored message, so cost grows with the size of the map
+fn query_storage_stats(deps: Deps) -> StdResult<StorageStatsResponse> {
+  let state = STATE.load(deps.storage)?;
+  
+  let mut message_count = 0u64;
+  let mut estimated_message_bytes = 0u64;
+  for item in MESSAGES.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+      let (_, message) = item?;
+      message_count += 1;
+      estimated_message_bytes += message.length;
+  }
+  
+  Ok(StorageStatsResponse {
+      message_count,
+      test_run_count: state.test_run_count,
+      estimated_message_bytes,
+  })
+}
+
+/// Fit gas = intercept + slope * bytes over (bytes, gas) points
+/// Returns None with fewer than two points, identical x values, or on overflow
+pub fn linear_fit(points: &[(u64, u128)]) -> Option<LinearFit> {
+  let n = points.len() as i128;
+  if n < 2 {
+      return None;
+  }
+  
+  let (mut sx, mut sy, mut sxx, mut sxy, mut syy) = (0i128, 0i128, 0i128, 0i128, 0i128);
+  for &(x, y) in points {
+      let (x, y) = (x as i128, i128::try_from(y).ok()?);
+      sx = sx.checked_add(x)?;
+      sy = sy.checked_add(y)?;
+      sxx = sxx.checked_add(x.checked_mul(x)?)?;
+      sxy = sxy.checked_add(x.checked_mul(y)?)?;
+      syy = syy.checked_add(y.checked_mul(y)?)?;
+  }
+  
+  let denom = n.checked_mul(sxx)?.checked_sub(sx.checked_mul(sx)?)?;
+  if denom == 0 {
+      return None;
+  }
+  
+  let slope_num = n.checked_mul(sxy)?.checked_sub(sx.checked_mul(sy)?)?;
+  let intercept_num = sy.checked_mul(sxx)?.checked_sub(sx.checked_mul(sxy)?)?;
+  let slope_scaled = slope_num.checked_mul(GAS_MODEL_SCALE)? / denom;
+  
+  // r^2 = slope_num^2 / (denom * y_denom), a constant y is fitted exactly
+  let y_denom = n.checked_mul(syy)?.checked_sub(sy.checked_mul(sy)?)?;
+  let r_squared_scaled = if y_denom == 0 {
+      GAS_MODEL_SCALE
+  } else {
+      slope_scaled.checked_mul(slope_num)? / y_denom
+  };
+  
+  Some(LinearFit {
+      intercept: intercept_num / denom,
+      slope_scaled,
+      r_squared_scaled,
+  })
+}
+
+/// Split a run's gas into fixed overhead, the GetGasModel intercept per message, and
+/// the payload gas left over, so the two parts always add up to the run's total_gas
+fn query_decompose_run(deps: Deps, run_id: String, chain: Option<&str>) -> StdResult<RunDecompositionResponse> {
+  let (_, run) = find_run(deps.storage, &run_id, chain)?
+      .ok_or_else(|| StdError::not_found(format!("test run {}", run_id)))?;
+  let model = query_gas_model(deps, None)?;
+  
+  // Overhead can't be negative or exceed what the run actually used
+  let overhead = (model.intercept.i128().max(0) as u128).saturating_mul(run.message_count as u128);
+  let overhead_gas = Uint128::new(overhead.min(run.total_gas.u128()));
+  
+  Ok(RunDecompositionResponse {
+      run_id,
+      overhead_gas,
+      payload_gas: run.total_gas.saturating_sub(overhead_gas),
+  })
+}
+
+/// Fit the gas model over recorded GasSamples
+fn query_gas_model(deps: Deps, run_id: Option<String>) -> StdResult<GasModelResponse> {
+  let samples: Vec<GasSample> = match run_id {
+      Some(run_id) => {
+          find_run(deps.storage, &run_id, None)?
+              .ok_or_else(|| StdError::generic_err(format!("test run not found: {}", run_id)))?
+              .1
+              .samples
+      }
+      None => {
+          let mut samples = vec![];
+          for item in TEST_RUNS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+              samples.extend(item?.1.samples);
+          }
+          samples
+      }
+  };
+  
+  let points: Vec<(u64, u128)> = samples.iter().map(|sample| (sample.bytes, sample.gas.u128())).collect();
+  let fit = linear_fit(&points).ok_or_else(|| {
+      StdError::generic_err("gas model unavailable: need at least two samples with distinct bytes")
+  })?;
+  
+  Ok(GasModelResponse {
+      intercept: Int128::new(fit.intercept),
+      slope: Int128::new(fit.slope_scaled),
+      r_squared: Uint128::new(fit.r_squared_scaled.max(0) as u128),
+      scale: Uint128::new(GAS_MODEL_SCALE as u128),
+      sample_count: points.len() as u64,
+  })
+}
+
+/// Predict gas for a payload size, preferring the sample fit over avg_gas_per_byte
+fn query_estimate_gas(deps: Deps, bytes: u64, chain: Option<&str>) -> StdResult<GasEstimateResponse> {
+  let runs = TEST_RUNS
+      .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .map(|item| item.map(|(_, run)| run))
+      .filter(|run| match run {
+          Ok(run) => on_chain(run, chain),
+          Err(_) => true,
+      })
+      .collect::<StdResult<Vec<_>>>()?;
+  
+  let sampled: Vec<&TestRunStats> = runs.iter().filter(|run| !run.samples.is_empty()).collect();
+  let points: Vec<(u64, u128)> = sampled
+      .iter()
+      .flat_map(|run| run.samples.iter().map(|sample| (sample.bytes, sample.gas.u128())))
+      .collect();
+  
+  if let Some(fit) = linear_fit(&points) {
+      let estimate = fit.intercept.saturating_add(fit.slope_scaled.saturating_mul(bytes as i128) / GAS_MODEL_SCALE);
+      return Ok(GasEstimateResponse {
+          estimated_gas: Uint128::new(estimate.max(0) as u128),
+          model: "linear_fit".to_string(),
+          runs_used: sampled.len() as u64,
+      });
+  }
+  
+  // Fall back to the mean per-byte rate of runs that reported one
+  let rates: Vec<Uint128> = runs
+      .iter()
+      .filter(|run| !run.avg_gas_per_byte.is_zero())
+      .map(|run| run.avg_gas_per_byte)
+      .collect();
+  if rates.is_empty() {
+      return Err(StdError::generic_err(ContractError::NoData {}.to_string()));
+  }
+  
+  let rate_sum = rates
+      .iter()
+      .try_fold(Uint128::zero(), |sum, rate| sum.checked_add(*rate))
+      .map_err(StdError::overflow)?;
+  let avg_gas_per_byte = rate_sum.u128() / rates.len() as u128;
+  Ok(GasEstimateResponse {
+      estimated_gas: Uint128::new(avg_gas_per_byte.saturating_mul(bytes as u128)),
+      model: "avg_gas_per_byte".to_string(),
+      runs_used: rates.len() as u64,
+  })
+}
+
+/// Price a gas estimate with the latest gas_price recorded for the chain
+fn query_estimate_fee(deps: Deps, bytes: u64, chain: Option<&str>) -> StdResult<Coin> {
+  let estimate = query_estimate_gas(deps, bytes, chain)?;
+  
+  // Latest by timestamp, later keys win ties
+  let mut latest: Option<(u64, Decimal, String)> = None;
+  for item in TEST_RUNS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+      let (_, run) = item?;
+      if !on_chain(&run, chain) {
+          continue;
+      }
+      if let (Some(price), Some(denom)) = (run.gas_price, run.fee_denom) {
+          if latest.as_ref().map_or(true, |(time, _, _)| run.timestamp >= *time) {
+              latest = Some((run.timestamp, price, denom));
+          }
+      }
+  }
+  
+  let (_, price, denom) = latest.ok_or_else(|| StdError::generic_err(ContractError::NoData {}.to_string()))?;
+  let amount = estimate
+      .estimated_gas
+      .checked_mul_ceil(price)
+      .map_err(|err| StdError::generic_err(err.to_string()))?;
+  
+  Ok(Coin { denom, amount })
+}
+
+// Compare two runs, `against` is treated as the newer one
+fn query_compare_runs(deps: Deps, base: String, against: String, chain: Option<&str>) -> StdResult<RunComparisonResponse> {
+  let load = |run_id: &str| -> StdResult<TestRunStats> {
+      let (_, run) = find_run(deps.storage, run_id, chain)?
+          .ok_or_else(|| StdError::generic_err(format!("test run not found: {}", run_id)))?;
+      if run.message_count == 0 {
+          return Err(StdError::generic_err(format!("test run has no messages: {}", run_id)));
+      }
+      Ok(run)
+  };
+  let base_run = load(&base)?;
+  let against_run = load(&against)?;
+  
+  compare_runs(deps, (base, base_run), (against, against_run))
+}
+
+// Compare the two most recent runs on a chain, later keys win timestamp ties
+fn query_latest_delta(deps: Deps, chain: &str) -> StdResult<RunComparisonResponse> {
+  let mut runs = TEST_RUNS
+      .prefix(&normalize_chain_id(chain))
+      .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+      .filter(|item| item.as_ref().map_or(true, |(_, run)| run.message_count > 0))
+      .collect::<StdResult<Vec<_>>>()?;
+  
+  // Stable sort keeps key order within a timestamp
+  runs.sort_by_key(|(_, run)| run.timestamp);
+  let against = runs.pop();
+  let base = runs.pop();
+  
+  match (base, against) {
+      (Some(base), Some(against)) => compare_runs(deps, base, against),
+      _ => Err(StdError::generic_err(format!("need at least two runs on chain: {}", chain))),
+  }
+}
+
+fn compare_runs(
+  deps: Deps,
+  (base, base_run): (String, TestRunStats),
+  (against, against_run): (String, TestRunStats),
+) -> StdResult<RunComparisonResponse> {
+  let threshold_bps = load_config(deps.storage)?.regression_threshold_bps;
+  
+  let avg_gas_per_byte = metric_delta(base_run.avg_gas_per_byte, against_run.avg_gas_per_byte);
+  let gas_per_message = metric_delta(
+      Uint128::new(base_run.total_gas.u128() / base_run.message_count as u128),
+      Uint128::new(against_run.total_gas.u128() / against_run.message_count as u128),
+  );
+  let message_count = metric_delta(
+      Uint128::from(base_run.message_count),
+      Uint128::from(against_run.message_count),
+  );
+  
+  let threshold = Int128::new(threshold_bps as i128);
+  let regression = avg_gas_per_byte.change_bps > threshold || gas_per_message.change_bps > threshold;
+  
+  Ok(RunComparisonResponse {
+      base,
+      against,
+      avg_gas_per_byte,
+      gas_per_message,
+      message_count,
+      threshold_bps,
+      regression,
+  })
+}
+
+fn metric_delta(base: Uint128, against: Uint128) -> MetricDelta {
+  // Deltas are reported as Int128, so magnitudes above i128::MAX saturate instead of wrapping
+  let signed = |magnitude: u128| {
+      let magnitude = i128::try_from(magnitude).unwrap_or(i128::MAX);
+      if against < base { -magnitude } else { magnitude }
+  };
+  let diff = against.u128().abs_diff(base.u128());
+  let change_bps = if base.is_zero() {
+      0
+  } else {
+      // Scaled in 256 bits so the percentage of a large delta stays exact
+      let bps = Uint256::from(diff) * Uint256::from(10_000u128) / Uint256::from(base);
+      signed(Uint128::try_from(bps).map_or(u128::MAX, |bps| bps.u128()))
+  };
+  
+  MetricDelta {
+      base,
+      against,
+      delta: Int128::new(signed(diff)),
+      change_bps: Int128::new(change_bps),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coins, from_json, SystemError, SystemResult, WasmQuery};
+
+    fn record_run_msg(run_id: &str, count: u64, gas: u128, avg_gas: u128, chain: &str) -> ExecuteMsg {
+        ExecuteMsg::RecordTestRun {
+            run_id: run_id.to_string(),
+            count,
+            gas: Uint128::new(gas),
+            avg_gas: Some(Uint128::new(avg_gas)),
+            chain: chain.to_string(),
+            tx_proofs: None,
+            tx_hashes: None,
+            byte_length: None,
+            overwrite: None,
+            code_size_bytes: None,
+            bytes: 0,
+            min_gas: None,
+            max_gas: None,
+            median_gas: None,
+            samples: vec![],
+            gas_price: None,
+            fee_denom: None,
+            tags: None,
+            note: None,
+            started_at: None,
+            finished_at: None,
+            node_version: None,
+            message_ids: None,
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = InstantiateMsg::default();
+
+        // Should succeed
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // Check state
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(state.owner, "creator");
+        assert_eq!(state.test_run_count, 0);
+        assert_eq!(state.last_test_timestamp, None);
+    }
+
+    #[test]
+    fn store_message() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Store valid message
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::StoreMessage { content: "test message".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+        ).unwrap();
+        assert_eq!(res.attributes.len(), 4);
+        assert_eq!(res.attributes[3].key, "stored_at");
+        assert_eq!(res.attributes[3].value, mock_env().block.time.seconds().to_string());
+
+        // Test too large message
+        let large_msg = "x".repeat((MAX_MESSAGE_SIZE + 1) as usize);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::StoreMessage { content: large_msg, idempotency_key: None, gas_used: None, dedup: None, compress: None },
+        ).unwrap_err();
+        
+        // Should return MessageTooLarge error
+        match err {
+            ContractError::MessageTooLarge { size, max } => {
+                assert_eq!(size, MAX_MESSAGE_SIZE + 1);
+                assert_eq!(max, MAX_MESSAGE_SIZE);
+            },
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fixed_length_message() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Test padding (content shorter than target)
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::StoreFixedLength { 
+                content: "test".to_string(), 
+                length: 10,
+                idempotency_key: None,
+            },
+        ).unwrap();
+        assert_eq!(res.attributes.len(), 4);
+        assert_eq!(res.attributes[3].value, mock_env().block.time.seconds().to_string());
+        
+        // Check the message was stored correctly
+        let msg_id = res.attributes[1].value.clone(); // id attribute
+        let query_res: MessageResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: msg_id }).unwrap()
+        ).unwrap();
+        assert_eq!(query_res.length, 10);
+        assert_eq!(query_res.content, "test      "); // 4 chars + 6 spaces
+
+        // Test truncation (content longer than target)
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::StoreFixedLength { 
+                content: "this is a longer test".to_string(), 
+                length: 7,
+                idempotency_key: None,
+            },
+        ).unwrap();
+        
+        let msg_id = res.attributes[1].value.clone();
+        let query_res: MessageResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: msg_id }).unwrap()
+        ).unwrap();
+        assert_eq!(query_res.length, 7);
+        assert_eq!(query_res.content, "this is"); // truncated to 7 chars
+    }
+
+    #[test]
+    fn test_clear_data() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Store some test data
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::StoreMessage { content: "test1".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+        ).unwrap();
+        
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::StoreMessage { content: "test2".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+        ).unwrap();
+
+        // Record a test run
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::RecordTestRun { 
+                run_id: "test_run_1".to_string(),
+                count: 2,
+                gas: Uint128::new(100000),
+                avg_gas: Some(Uint128::new(50000)),
+                chain: "test-chain".to_string(),
+                tx_proofs: Some(vec!["tx1".to_string(), "tx2".to_string()]),
+                tx_hashes: None,
+                byte_length: None,
+                overwrite: None,
+                code_size_bytes: None,
+                bytes: 0,
+                min_gas: None,
+                max_gas: None,
+                median_gas: None,
+                samples: vec![],
+                gas_price: None,
+                fee_denom: None,
+                tags: None,
+                note: None,
+                started_at: None,
+                finished_at: None,
+                node_version: None,
+                message_ids: None,
+            },
+        ).unwrap();
+
+        // Test unauthorized clear
+        let unauth_info = mock_info("someone_else", &coins(1000, "earth"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            unauth_info,
+            ExecuteMsg::ClearData { limit: None },
+        ).unwrap_err();
+        
+        // Should return Unauthorized error
+        match err {
+            ContractError::Unauthorized {} => {},
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        // Test authorized clear
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::ClearData { limit: None },
+        ).unwrap();
+        assert_eq!(res.attributes.len(), 5);
+        assert_eq!(res.attributes[4].value, "true");
+
+        // Verify data was cleared - count should be 0
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.test_count, 0);
+
+        // Verify gas summary is reset
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        assert_eq!(summary.msg_count, 0);
+        assert_eq!(summary.total_gas, Uint128::zero());
+    }
+
+    #[test]
+    fn idempotent_store() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        // First store with a key writes the message
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::StoreMessage {
+                content: "test".to_string(),
+                idempotency_key: Some("key1".to_string()),
+                gas_used: None,
+                dedup: None,
+                compress: None,
+            },
+        ).unwrap();
+        let first_id = res.attributes[1].value.clone();
+
+        // Retry in a later block with the same key replays the original id
+        let mut env = mock_env();
+        env.block.height += 1;
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::StoreMessage {
+                content: "test".to_string(),
+                idempotency_key: Some("key1".to_string()),
+                gas_used: None,
+                dedup: None,
+                compress: None,
+            },
+        ).unwrap();
+        assert_eq!(res.attributes[1].value, first_id);
+        assert_eq!(res.attributes[2].key, "idempotent_replay");
+        assert_eq!(res.attributes[2].value, "true");
+
+        let count = MESSAGES
+            .keys(deps.as_ref().storage, None, None, cosmwasm_std::Order::Ascending)
+            .count();
+        assert_eq!(count, 1);
+
+        // A different key writes a second entry
+        execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::StoreMessage {
+                content: "test".to_string(),
+                idempotency_key: Some("key2".to_string()),
+                gas_used: None,
+                dedup: None,
+                compress: None,
+            },
+        ).unwrap();
+
+        let count = MESSAGES
+            .keys(deps.as_ref().storage, None, None, cosmwasm_std::Order::Ascending)
+            .count();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn gas_curve() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        // Record the longer length first so ordering comes from the query
+        for (run_id, length, avg_gas) in [("run_a", 1000u64, 40u128), ("run_b", 10, 900), ("run_c", 1000, 42)] {
+            let mut msg = record_run_msg(run_id, 1, avg_gas * length as u128, avg_gas, "test-chain");
+            if let ExecuteMsg::RecordTestRun { byte_length, .. } = &mut msg {
+                *byte_length = Some(length);
+            }
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        // Runs without a length are left off the curve
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_d", 1, 1000, 1, "test-chain")).unwrap();
+
+        let curve: GasCurveResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasCurve {}).unwrap()
+        ).unwrap();
+        assert_eq!(curve.points, vec![
+            GasCurvePoint { length: 10, avg_gas_per_byte: Uint128::new(900), runs: 1 },
+            GasCurvePoint { length: 1000, avg_gas_per_byte: Uint128::new(41), runs: 2 },
+        ]);
+    }
+
+    #[test]
+    fn message_block_span() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        // Empty contract reports zeros
+        let span: MessageBlockSpanResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetMessageBlockSpan {}).unwrap()
+        ).unwrap();
+        assert_eq!(span, MessageBlockSpanResponse { first_height: 0, last_height: 0, block_span: 0 });
+
+        for height in [150, 100] {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+
+        let span: MessageBlockSpanResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetMessageBlockSpan {}).unwrap()
+        ).unwrap();
+        assert_eq!(span, MessageBlockSpanResponse { first_height: 100, last_height: 150, block_span: 50 });
+    }
+
+    #[test]
+    fn duplicate_run_id() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 1, 1000, 10, "test-chain")).unwrap();
+
+        // Re-recording without overwrite is rejected
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            record_run_msg("run_1", 2, 2000, 20, "test-chain"),
+        ).unwrap_err();
+        assert_eq!(err, ContractError::RunExists { run_id: "run_1".to_string() });
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.test_count, 1);
+
+        // Explicit overwrite replaces the run without bumping the count
+        let mut msg = record_run_msg("run_1", 2, 2000, 20, "test-chain");
+        if let ExecuteMsg::RecordTestRun { overwrite, .. } = &mut msg {
+            *overwrite = Some(true);
+        }
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.test_count, 1);
+
+        let run = find_run(deps.as_ref().storage, "run_1", None).unwrap().unwrap().1;
+        assert_eq!(run.message_count, 2);
+        assert_eq!(run.total_gas, Uint128::new(2000));
+    }
+
+    #[test]
+    fn update_test_run() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let update = |run_id: &str| ExecuteMsg::UpdateTestRun {
+            run_id: run_id.to_string(),
+            count: 3,
+            gas: Uint128::new(3000),
+            avg_gas: Some(Uint128::new(30)),
+            chain: "test-chain".to_string(),
+            tx_proofs: None,
+            tx_hashes: None,
+            byte_length: None,
+            code_size_bytes: None,
+            bytes: 0,
+            min_gas: None,
+            max_gas: None,
+            median_gas: None,
+            samples: vec![],
+            gas_price: None,
+            fee_denom: None,
+        };
+
+        // Only existing runs can be updated
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), update("run_1")).unwrap_err();
+        assert_eq!(err, ContractError::RunNotFound { run_id: "run_1".to_string() });
+
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 1, 1000, 10, "test-chain")).unwrap();
+        let recorded_at = mock_env().block.time.seconds();
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(600);
+        let res = execute(deps.as_mut(), later.clone(), info.clone(), update("run_1")).unwrap();
+        assert_eq!(res.attributes[5].value, "3000");
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.test_count, 1);
+
+        // The amendment keeps the original timestamp
+        let run = find_run(deps.as_ref().storage, "run_1", None).unwrap().unwrap().1;
+        assert_eq!(run.message_count, 3);
+        assert_eq!(run.timestamp, recorded_at);
+        assert_eq!(config.last_test, Some(recorded_at));
+
+        // avg_gas goes through the same derivation and tolerance as RecordTestRun
+        let with_bytes = |avg_gas: Option<u128>| {
+            let mut msg = update("run_1");
+            if let ExecuteMsg::UpdateTestRun { avg_gas: avg, bytes, .. } = &mut msg {
+                *avg = avg_gas.map(Uint128::new);
+                *bytes = 300;
+            }
+            msg
+        };
+        execute(deps.as_mut(), later.clone(), info.clone(), with_bytes(None)).unwrap();
+        let run = find_run(deps.as_ref().storage, "run_1", None).unwrap().unwrap().1;
+        assert_eq!(run.avg_gas_per_byte, Uint128::new(10));
+        let err = execute(deps.as_mut(), later.clone(), info.clone(), with_bytes(Some(30))).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidGasValue(_)));
+        let err = execute(deps.as_mut(), later, info, update("run 1")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRunId(_)));
+    }
+
+    #[test]
+    fn store_and_read() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::StoreAndRead { content: "abc".to_string(), reads: 10 },
+        ).unwrap();
+        assert_eq!(res.attributes[3].value, "10");
+        // 'a' + 'b' + 'c' = 97 + 98 + 99
+        assert_eq!(res.attributes[4].value, "294");
+
+        // Read count above the bound is rejected
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::StoreAndRead { content: "abc".to_string(), reads: MAX_READS + 1 },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::LimitExceeded { 
+            requested: (MAX_READS + 1) as u64, 
+            max: MAX_READS as u64 
+        });
+    }
+
+    #[test]
+    fn gas_vs_code_size() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for (run_id, code_size, gas) in [("run_a", 250_000u64, 5000u128), ("run_b", 180_000, 4000)] {
+            let mut msg = record_run_msg(run_id, 1, gas, 10, "test-chain");
+            if let ExecuteMsg::RecordTestRun { code_size_bytes, .. } = &mut msg {
+                *code_size_bytes = Some(code_size);
+            }
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        let res: GasVsCodeSizeResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasVsCodeSize {}).unwrap()
+        ).unwrap();
+        assert_eq!(res.points, vec![
+            CodeSizePoint { code_size: 180_000, total_gas: Uint128::new(4000) },
+            CodeSizePoint { code_size: 250_000, total_gas: Uint128::new(5000) },
+        ]);
+
+        // Code size is surfaced on the run itself
+        let runs: TestRunsResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRuns { start_after: None, limit: None, chain: None, order: None, sort: None, tag: None, node_version: None, include_proofs: None }).unwrap()
+        ).unwrap();
+        assert_eq!(runs.runs[0].code_size, Some(180_000));
+    }
+
+    #[test]
+    fn avg_bytes_per_msg() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        // 1000 / 10 = 100 bytes over 2 msgs, 3000 / 20 = 150 bytes over 3 msgs
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 2, 1000, 10, "test-chain")).unwrap();
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_2", 3, 3000, 20, "test-chain")).unwrap();
+
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        assert_eq!(summary.total_bytes, 250);
+        assert_eq!(summary.msg_count, 5);
+        assert_eq!(summary.avg_bytes_per_msg, 50);
+    }
+
+    #[test]
+    fn read_messages() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let mut ids = vec![];
+        for (height, content) in [(100, "abc"), (101, "defgh")] {
+            let mut env = mock_env();
+            env.block.height = height;
+            let res = execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: content.to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+            ids.push(res.attributes[1].value.clone());
+        }
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ReadMessages { ids: ids.clone() },
+        ).unwrap();
+        assert_eq!(res.attributes[1].value, "2");
+        assert_eq!(res.attributes[2].value, "8");
+
+        // First missing id is reported
+        ids.push("msg_missing".to_string());
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ReadMessages { ids },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::MessageNotFound { id: "msg_missing".to_string() });
+
+        // Oversized id list is rejected
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::ReadMessages { ids: vec!["msg_100".to_string(); MAX_READ_IDS + 1] },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::LimitExceeded { 
+            requested: (MAX_READ_IDS + 1) as u64, 
+            max: MAX_READ_IDS as u64 
+        });
+    }
+
+    #[test]
+    fn health() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_1", 1, 1000, 10, "test-chain")).unwrap();
+
+        let health: HealthResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetHealth {}).unwrap()
+        ).unwrap();
+        assert_eq!(health, HealthResponse { status: HealthStatus::Healthy, reason: None });
+
+        // Drift the cached count away from the stored runs
+        let mut state = STATE.load(deps.as_ref().storage).unwrap();
+        state.test_run_count = 3;
+        STATE.save(deps.as_mut().storage, &state).unwrap();
+
+        let health: HealthResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetHealth {}).unwrap()
+        ).unwrap();
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert!(health.reason.is_some());
+    }
+
+    #[test]
+    fn iterate_messages() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for (height, content) in [(100, "a"), (101, "bb"), (102, "cccc")] {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: content.to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+
+        // Descending visits the two newest ids
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::IterateMessages { limit: 2, ascending: false },
+        ).unwrap();
+        assert_eq!(res.attributes[1].value, "2");
+        assert_eq!(res.attributes[2].value, "6");
+
+        // Limit past the end visits everything
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::IterateMessages { limit: 10, ascending: true },
+        ).unwrap();
+        assert_eq!(res.attributes[1].value, "3");
+        assert_eq!(res.attributes[2].value, "7");
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::IterateMessages { limit: MAX_ITERATE + 1, ascending: true },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::LimitExceeded { 
+            requested: (MAX_ITERATE + 1) as u64, 
+            max: MAX_ITERATE as u64 
+        });
+    }
+
+    #[test]
+    fn list_messages_window() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for height in 100..105 {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+
+        let res: ListMessagesResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::ListMessages {
+                start_after: Some("msg_100".to_string()),
+                end_before: Some("msg_104".to_string()),
+                limit: None,
+                include_archived: None,
+            }).unwrap()
+        ).unwrap();
+        let ids: Vec<_> = res.msgs.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["msg_101", "msg_102", "msg_103"]);
+        assert_eq!(res.count, 3);
+
+        // Inverted window is rejected
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::ListMessages {
+            start_after: Some("msg_104".to_string()),
+            end_before: Some("msg_100".to_string()),
+            limit: None,
+            include_archived: None,
+        }).unwrap_err();
+        assert!(matches!(err, StdError::GenericErr { .. }));
+    }
+
+    #[test]
+    fn reset_counter() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for _ in 0..3 {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::IncrementCounter { name: "hits".to_string() },
+            ).unwrap();
+        }
+
+        // Only the owner can reset
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone_else", &[]),
+            ExecuteMsg::ResetCounter { name: "hits".to_string() },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ResetCounter { name: "hits".to_string() },
+        ).unwrap();
+        assert_eq!(res.attributes[2].value, "3");
+
+        let counter: CounterResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetCounter { name: "hits".to_string() }).unwrap()
+        ).unwrap();
+        assert_eq!(counter.value, 0);
+
+        // Resetting an absent counter is a no-op
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::ResetCounter { name: "missing".to_string() },
+        ).unwrap();
+        assert_eq!(res.attributes[2].value, "0");
+    }
+
+    #[test]
+    fn delete_range() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for height in 100..105 {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone_else", &[]),
+            ExecuteMsg::DeleteRange { start_after: None, limit: 2 },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::DeleteRange { start_after: None, limit: 2 },
+        ).unwrap();
+        assert_eq!(res.attributes[1].value, "2");
+        assert_eq!(res.attributes[2].value, "8");
+        assert_eq!(res.attributes[3].value, "msg_101");
+
+        // Continue from the returned cursor
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::DeleteRange { start_after: Some("msg_102".to_string()), limit: 10 },
+        ).unwrap();
+        assert_eq!(res.attributes[1].value, "2");
+        assert_eq!(res.attributes[3].value, "msg_104");
+
+        let remaining: Vec<String> = MESSAGES
+            .keys(deps.as_ref().storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(remaining, vec!["msg_102".to_string()]);
+    }
+
+    #[test]
+    fn contract_version() {
+        let mut deps = mock_dependencies();
+
+        // Contracts instantiated before versioning have nothing stored
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::GetVersion {}).unwrap_err();
+        assert!(err.to_string().contains("contract version not set"));
+
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+
+        let version: VersionResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetVersion {}).unwrap()
+        ).unwrap();
+        assert_eq!(version, VersionResponse {
+            contract: CONTRACT_NAME.to_string(),
+            version: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    #[test]
+    fn decompose_run() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        // Samples follow 100000 + 40 * bytes exactly, the same fit GetGasModel reports
+        let sampled = |run_id: &str, count: u64, gas: u128, chain: &str| {
+            let mut msg = record_run_msg(run_id, count, gas, 40, chain);
+            if let ExecuteMsg::RecordTestRun { avg_gas, byte_length, samples, .. } = &mut msg {
+                *avg_gas = None;
+                *byte_length = Some(100);
+                *samples = vec![
+                    GasSample { bytes: 100, gas: Uint128::new(104_000) },
+                    GasSample { bytes: 1000, gas: Uint128::new(140_000) },
+                ];
+            }
+            msg
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), sampled("run_a", 2, 208_000, "test-chain")).unwrap();
+        let model: GasModelResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetGasModel { run_id: None }).unwrap()).unwrap();
+        assert_eq!(model.intercept, Int128::new(100_000));
+
+        let decompose = |deps: Deps, run_id: &str, chain: Option<&str>| {
+            let msg = QueryMsg::DecomposeRun { run_id: run_id.to_string(), chain: chain.map(str::to_string) };
+            query(deps, mock_env(), msg).map(|res| from_json::<RunDecompositionResponse>(res).unwrap())
+        };
+        let res = decompose(deps.as_ref(), "run_a", None).unwrap();
+        assert_eq!(res.overhead_gas, Uint128::new(200_000));
+        assert_eq!(res.payload_gas, Uint128::new(8_000));
+
+        // The same id on a second chain needs the chain, and a run below the modelled
+        // overhead is all overhead; the parts always add up to total_gas
+        execute(deps.as_mut(), mock_env(), info, sampled("run_a", 1, 50_000, "other-chain")).unwrap();
+        let err = decompose(deps.as_ref(), "run_a", None).unwrap_err();
+        assert!(err.to_string().contains("specify a chain"));
+        let res = decompose(deps.as_ref(), "run_a", Some("other-chain")).unwrap();
+        assert_eq!((res.overhead_gas, res.payload_gas), (Uint128::new(50_000), Uint128::zero()));
+        let res = decompose(deps.as_ref(), "run_a", Some("test-chain")).unwrap();
+        assert_eq!(res.overhead_gas + res.payload_gas, Uint128::new(208_000));
+
+        // Missing run
+        decompose(deps.as_ref(), "nope", None).unwrap_err();
+    }
+
+    #[test]
+    fn decompose_run_without_model() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_a", 1, 1000, 10, "test-chain")).unwrap();
+
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::DecomposeRun { run_id: "run_a".to_string(), chain: None }).unwrap_err();
+        assert!(err.to_string().contains("gas model unavailable"));
+    }
+
+    #[test]
+    fn gas_std_dev() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        // Single run has no dispersion
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_0", 1, 2000, 10, "test-chain")).unwrap();
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        assert_eq!(summary.gas_std_dev, Uint128::zero());
+
+        // 2,4,4,4,5,5,7,9 (x1000): mean 5000, variance 4_000_000, std dev 2000
+        for (i, gas) in [4000u128, 4000, 4000, 5000, 5000, 7000, 9000].iter().enumerate() {
+            let run_id = format!("run_{}", i + 1);
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(&run_id, 1, *gas, 10, "test-chain")).unwrap();
+        }
+
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        assert_eq!(summary.gas_std_dev, Uint128::new(2000));
+    }
+
+    #[test]
+    fn instantiate_owner_override() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("relayer", &[]);
+
+        let res = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            InstantiateMsg { owner: Some("admin".to_string()), ..Default::default() },
+        ).unwrap();
+        assert_eq!(res.attributes[1].value, "admin");
+
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(state.owner, "admin");
+
+        // Sender doesn't get owner rights
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClearData { limit: None }).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Invalid owner address is rejected
+        let mut deps = mock_dependencies();
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("relayer", &[]),
+            InstantiateMsg { owner: Some("".to_string()), ..Default::default() },
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+    }
+
+    #[test]
+    fn weighted_gas_per_byte() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        // avg_gas here is payload gas alone, so it sits well below gas / bytes
+        let widest = ExecuteMsg::UpdateConfig {
+            max_message_size: None,
+            max_list_limit: None,
+            max_runs_limit: None,
+            max_messages: None,
+            eviction: None,
+            hex_tx_proofs: None,
+            strict_validation: None,
+            regression_threshold_bps: None,
+            min_gas_per_byte: None,
+            max_gas_per_byte: None,
+            avg_gas_tolerance_bps: Some(MAX_AVG_GAS_TOLERANCE_BPS),
+            open_recording: None,
+            max_proof_bytes: None,
+            gas_budget: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), widest).unwrap();
+
+        // Small overhead-heavy run and a large run with reported byte counts
+        for (run_id, gas, avg_gas, run_bytes) in [("run_a", 50_000u128, 10u128, 100u64), ("run_b", 60_000, 20, 1000)] {
+            let mut msg = record_run_msg(run_id, 1, gas, avg_gas, "test-chain");
+            if let ExecuteMsg::RecordTestRun { bytes, .. } = &mut msg {
+                *bytes = run_bytes;
+            }
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        // Pooled: 110000 gas / (100 + 1000) reported bytes
+        assert_eq!(summary.gas_per_byte, Uint128::new(100));
+        // Weighted: (10 * 100 + 20 * 1000) / 1100
+        assert_eq!(summary.weighted_gas_per_byte, Uint128::new(19));
+    }
+
+    #[test]
+    fn storage_stats() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for (height, content) in [(100, "hello"), (101, "benchmark payload")] {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: content.to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_1", 2, 1000, 10, "test-chain")).unwrap();
+
+        let stats: StorageStatsResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetStorageStats {}).unwrap()
+        ).unwrap();
+        assert_eq!(stats, StorageStatsResponse {
+            message_count: 2,
+            test_run_count: 1,
+            estimated_message_bytes: 5 + 17,
+        });
+    }
+
+    #[test]
+    fn update_config() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.max_message_size, MAX_MESSAGE_SIZE);
+        assert_eq!(config.max_list_limit, DEFAULT_MAX_LIST_LIMIT);
+        assert_eq!(config.max_runs_limit, DEFAULT_MAX_RUNS_LIMIT);
+
+        let update = ExecuteMsg::UpdateConfig {
+            max_message_size: Some(20_000),
+            max_list_limit: Some(2),
+            max_runs_limit: None,
+            max_messages: None,
+            eviction: None,
+            hex_tx_proofs: None,
+            strict_validation: None,
+            regression_threshold_bps: None,
+            min_gas_per_byte: None,
+            max_gas_per_byte: None,
+            avg_gas_tolerance_bps: None,
+            open_recording: None,
+            max_proof_bytes: None,
+            gas_budget: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone_else", &[]), update.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), info.clone(), update).unwrap();
+
+        // Larger messages are now accepted
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::StoreMessage { content: "x".repeat(15_000), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+        ).unwrap();
+
+        // List page size is clamped to the new limit
+        for height in 100..104 {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+        let res: ListMessagesResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::ListMessages { start_after: None, end_before: None, limit: Some(30), include_archived: None }).unwrap()
+        ).unwrap();
+        assert_eq!(res.count, 2);
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.max_message_size, 20_000);
+        assert_eq!(config.max_list_limit, 2);
+        assert_eq!(config.max_runs_limit, DEFAULT_MAX_RUNS_LIMIT);
+
+        // Run pages are clamped to max_runs_limit the same way
+        for run in 0..4 {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(&format!("run_{}", run), 1, 1000, 10, "test-chain")).unwrap();
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::UpdateConfig { max_message_size: None, max_list_limit: None, max_runs_limit: Some(3), max_messages: None, eviction: None, hex_tx_proofs: None, strict_validation: None, regression_threshold_bps: None, min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: None },
+        ).unwrap();
+        let res: TestRunsResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRuns { start_after: None, limit: Some(20), chain: None, order: None, sort: None, tag: None, node_version: None, include_proofs: None }).unwrap()
+        ).unwrap();
+        assert_eq!(res.runs.len(), 3);
+
+        // Nonsensical values are rejected
+        for update in [
+            ExecuteMsg::UpdateConfig { max_message_size: Some(0), max_list_limit: None, max_runs_limit: None, max_messages: None, eviction: None, hex_tx_proofs: None, strict_validation: None, regression_threshold_bps: None, min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: None },
+            ExecuteMsg::UpdateConfig { max_message_size: Some(MAX_MESSAGE_SIZE_CEILING + 1), max_list_limit: None, max_runs_limit: None, max_messages: None, eviction: None, hex_tx_proofs: None, strict_validation: None, regression_threshold_bps: None, min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: None },
+            ExecuteMsg::UpdateConfig { max_message_size: None, max_list_limit: None, max_runs_limit: Some(MAX_PAGE_LIMIT_CEILING + 1), max_messages: None, eviction: None, hex_tx_proofs: None, strict_validation: None, regression_threshold_bps: None, min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: None },
+        ] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), update).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidConfig(_)));
+        }
+    }
+
+    #[test]
+    fn max_messages() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            InstantiateMsg { max_messages: Some(2), ..Default::default() },
+        ).unwrap();
+
+        let store_at = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, height: u64| {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(
+                deps.as_mut(),
+                env,
+                mock_info("anyone", &[]),
+                ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            )
+        };
+
+        store_at(&mut deps, 100).unwrap();
+        store_at(&mut deps, 101).unwrap();
+        // Overwriting an existing id doesn't count against the cap
+        store_at(&mut deps, 101).unwrap();
+
+        let err = store_at(&mut deps, 102).unwrap_err();
+        assert_eq!(err, ContractError::StorageFull { current: 2, max: 2 });
+
+        // Freeing a slot allows the store
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::DeleteRange { start_after: None, limit: 1 },
+        ).unwrap();
+        store_at(&mut deps, 102).unwrap();
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.max_messages, Some(2));
+        assert_eq!(config.message_count, 2);
+    }
+
+    #[test]
+    fn pause() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone_else", &[]),
+            ExecuteMsg::SetPaused { paused: true },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::SetPaused { paused: true }).unwrap();
+
+        let store = ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), store.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+
+        // Queries keep working and report the pause
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert!(config.paused);
+
+        let health: HealthResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetHealth {}).unwrap()
+        ).unwrap();
+        assert_eq!(health.status, HealthStatus::Frozen);
+
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::SetPaused { paused: false }).unwrap();
+        execute(deps.as_mut(), mock_env(), info, store).unwrap();
+    }
+
+    #[test]
+    fn recorders() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let ci = mock_info("ci_key", &[]);
+
+        // Not a recorder yet
+        let err = execute(deps.as_mut(), mock_env(), ci.clone(), record_run_msg("run_1", 1, 1000, 10, "test-chain")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Recorders can't manage recorders
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            ci.clone(),
+            ExecuteMsg::UpdateRecorders { add: vec!["ci_key".to_string()], remove: vec![] },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::UpdateRecorders { add: vec!["ci_key".to_string(), "ci_key".to_string()], remove: vec![] },
+        ).unwrap();
+
+        let res: RecordersResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::ListRecorders {}).unwrap()
+        ).unwrap();
+        assert_eq!(res.recorders, vec!["ci_key".to_string()]);
+
+        // Recorder can record but not clear
+        execute(deps.as_mut(), mock_env(), ci.clone(), record_run_msg("run_1", 1, 1000, 10, "test-chain")).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), ci.clone(), ExecuteMsg::ClearData { limit: None }).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Removed recorders lose access
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::UpdateRecorders { add: vec![], remove: vec!["ci_key".to_string()] },
+        ).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), ci, record_run_msg("run_2", 1, 1000, 10, "test-chain")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn eviction() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::UpdateConfig {
+                max_message_size: None,
+                max_list_limit: None,
+                max_runs_limit: None,
+                max_messages: Some(2),
+                eviction: Some(true),
+                hex_tx_proofs: None,
+                strict_validation: None,
+                regression_threshold_bps: None,
+                min_gas_per_byte: None,
+                max_gas_per_byte: None,
+                avg_gas_tolerance_bps: None,
+                open_recording: None,
+                max_proof_bytes: None,
+                gas_budget: None,
+            },
+        ).unwrap();
+
+        let mut last = None;
+        for height in 100..103 {
+            let mut env = mock_env();
+            env.block.height = height;
+            last = Some(execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap());
+        }
+
+        // Third store evicted the oldest
+        let res = last.unwrap();
+        assert_eq!(res.attributes[4].key, "evicted_id");
+        assert_eq!(res.attributes[4].value, "msg_100");
+
+        let ids: Vec<String> = MESSAGES
+            .keys(deps.as_ref().storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(ids, vec!["msg_101".to_string(), "msg_102".to_string()]);
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.message_count, 2);
+        assert!(config.eviction);
+    }
+
+    #[test]
+    fn validate_message() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+
+        let res: ValidateMessageResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::ValidateMessage { content: "hello".to_string() }).unwrap()
+        ).unwrap();
+        assert_eq!(res, ValidateMessageResponse { valid: true, length: 5, reason: None });
+
+        let res: ValidateMessageResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::ValidateMessage {
+                content: "x".repeat((MAX_MESSAGE_SIZE + 1) as usize),
+            }).unwrap()
+        ).unwrap();
+        assert!(!res.valid);
+        assert_eq!(res.length, MAX_MESSAGE_SIZE + 1);
+        assert!(res.reason.unwrap().contains("Message too large"));
+
+        // Nothing was written
+        let stats: StorageStatsResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetStorageStats {}).unwrap()
+        ).unwrap();
+        assert_eq!(stats.message_count, 0);
+    }
+
+    #[test]
+    fn renounce_ownership() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::UpdateRecorders { add: vec!["ci_key".to_string()], remove: vec![] },
+        ).unwrap();
+
+        // Wrong confirmation is rejected
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::RenounceOwnership { confirm: "yes".to_string() },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::InvalidConfirmation { expected: "renounce".to_string() });
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::RenounceOwnership { confirm: "renounce".to_string() },
+        ).unwrap();
+
+        // Owner and recorder gated actions are closed for good
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClearData { limit: None }).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 1, 1000, 10, "test-chain")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("ci_key", &[]), record_run_msg("run_1", 1, 1000, 10, "test-chain")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Public stores keep working
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+        ).unwrap();
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert!(config.renounced);
+
+        let health: HealthResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetHealth {}).unwrap()
+        ).unwrap();
+        assert_eq!(health.status, HealthStatus::Archived);
+    }
+
+    #[test]
+    fn clear_data_in_batches() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for i in 0..100u64 {
+            let mut env = mock_env();
+            env.block.height = 1000 + i;
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+        for i in 0..40 {
+            let run_id = format!("run_{}", i);
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(&run_id, 1, 1000, 10, "test-chain")).unwrap();
+        }
+
+        let mut calls = 0;
+        let mut messages_removed = 0;
+        loop {
+            let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClearData { limit: Some(30) }).unwrap();
+            calls += 1;
+            messages_removed += res.attributes[2].value.parse::<u64>().unwrap();
+            assert!(res.attributes[2].value.parse::<u64>().unwrap() <= 30);
+            if res.attributes[4].value == "true" {
+                break;
+            }
+            assert!(calls < 10);
+        }
+        assert_eq!(calls, 4);
+        assert_eq!(messages_removed, 100);
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.test_count, 0);
+        assert_eq!(config.message_count, 0);
+    }
+
+    #[test]
+    fn list_messages_since() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let base = mock_env().block.time;
+        for (height, offset) in [(100, 0), (101, 0), (102, 60), (103, 120)] {
+            let mut env = mock_env();
+            env.block.height = height;
+            env.block.time = base.plus_seconds(offset);
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+
+        let res: ListMessagesResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::ListMessagesSince { since: base.seconds(), limit: None }).unwrap()
+        ).unwrap();
+        let ids: Vec<_> = res.msgs.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["msg_102", "msg_103"]);
+
+        // Limit counts matching rows
+        let res: ListMessagesResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::ListMessagesSince { since: base.seconds(), limit: Some(1) }).unwrap()
+        ).unwrap();
+        assert_eq!(res.count, 1);
+        assert_eq!(res.msgs[0].id, "msg_102");
+    }
+
+    #[test]
+    fn selective_clear() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for height in [100, 101, 200] {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+        for run_id in ["osmo_1", "osmo_2", "juno_1"] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 1, 1000, 10, "test-chain")).unwrap();
+        }
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone_else", &[]),
+            ExecuteMsg::ClearMessages { prefix: None, limit: None },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Prefix scoped message clear leaves runs and other ids alone
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ClearMessages { prefix: Some("msg_10".to_string()), limit: None },
+        ).unwrap();
+        assert_eq!(res.attributes[1].value, "2");
+        assert_eq!(res.attributes[2].value, "true");
+
+        let ids: Vec<String> = MESSAGES
+            .keys(deps.as_ref().storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(ids, vec!["msg_200".to_string()]);
+
+        // Batched run clear by prefix
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ClearTestRuns { prefix: Some("osmo_".to_string()), limit: Some(1) },
+        ).unwrap();
+        assert_eq!(res.attributes[1].value, "1");
+        assert_eq!(res.attributes[2].value, "false");
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::ClearTestRuns { prefix: Some("osmo_".to_string()), limit: Some(1) },
+        ).unwrap();
+
+        let runs: Vec<String> = RUN_CHAINS
+            .keys(deps.as_ref().storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(runs, vec!["juno_1".to_string()]);
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.test_count, 1);
+        assert_eq!(config.message_count, 1);
+    }
+
+    #[test]
+    fn update_message() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::StoreMessage { content: "short".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+        ).unwrap();
+        let id = res.attributes[1].value.clone();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(30);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::UpdateMessage { id: id.clone(), content: "a longer payload".to_string() },
+        ).unwrap();
+
+        let msg: MessageResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: id.clone() }).unwrap()
+        ).unwrap();
+        assert_eq!(msg.content, "a longer payload");
+        assert_eq!(msg.length, 16);
+        assert_eq!(msg.time, env.block.time.seconds());
+
+        // Missing id
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::UpdateMessage { id: "msg_missing".to_string(), content: "x".to_string() },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::NoData {});
+
+        // Oversized content
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::UpdateMessage { id, content: "x".repeat((MAX_MESSAGE_SIZE + 1) as usize) },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::MessageTooLarge { size: MAX_MESSAGE_SIZE + 1, max: MAX_MESSAGE_SIZE });
+    }
+
+    #[test]
+    fn clear_history() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for (height, content) in [(100, "abc"), (101, "defgh")] {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: content.to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 1, 1000, 10, "test-chain")).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClearData { limit: None }).unwrap();
+
+        let history: ClearHistoryResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetClearHistory {}).unwrap()
+        ).unwrap();
+        assert_eq!(history.events, vec![ClearEvent {
+            timestamp: mock_env().block.time.seconds(),
+            messages_removed: 2,
+            runs_removed: 1,
+            bytes_freed: 8,
+        }]);
+
+        // History is bounded
+        for _ in 0..MAX_CLEAR_HISTORY + 5 {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::ClearTestRuns { prefix: None, limit: None },
+            ).unwrap();
+        }
+        let history: ClearHistoryResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetClearHistory {}).unwrap()
+        ).unwrap();
+        assert_eq!(history.events.len(), MAX_CLEAR_HISTORY as usize);
+        assert_eq!(history.events[0].runs_removed, 0);
+    }
+
+    #[test]
+    fn cumulative_gas_attribute() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 2, 1500, 10, "test-chain")).unwrap();
+        assert_eq!(res.attributes[5].key, "cumulative_gas");
+        assert_eq!(res.attributes[5].value, "1500");
+
+        let res = execute(deps.as_mut(), mock_env(), info, record_run_msg("run_2", 2, 2500, 10, "test-chain")).unwrap();
+        assert_eq!(res.attributes[5].value, "4000");
+    }
+
+    #[test]
+    fn append_tx_proofs() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let mut msg = record_run_msg("run_1", 2, 1000, 10, "test-chain");
+        if let ExecuteMsg::RecordTestRun { tx_proofs, .. } = &mut msg {
+            *tx_proofs = Some(vec!["tx1".to_string(), "tx2".to_string()]);
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::AppendTxProofs { run_id: "run_1".to_string(), proofs: vec!["tx3".to_string()], chain: None },
+        ).unwrap();
+        assert_eq!(res.attributes[2].value, "3");
+        let run = find_run(deps.as_ref().storage, "run_1", None).unwrap().unwrap().1;
+        assert_eq!(run.tx_proofs, vec!["tx1", "tx2", "tx3"]);
+
+        // Empty proofs and going over the cap are rejected
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::AppendTxProofs { run_id: "run_1".to_string(), proofs: vec!["".to_string()], chain: None },
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTxProof(_)));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::AppendTxProofs { run_id: "run_1".to_string(), proofs: vec!["tx".to_string(); MAX_TX_PROOFS], chain: None },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::TooManyProofs { count: MAX_TX_PROOFS as u64 + 3, max: MAX_TX_PROOFS as u64 });
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::AppendTxProofs { run_id: "run_missing".to_string(), proofs: vec!["tx".to_string()], chain: None },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::RunNotFound { run_id: "run_missing".to_string() });
+
+        // Runs stored before proofs became a list still load
+        let legacy: TestRunStats = from_json(
+            br#"{"timestamp":1,"message_count":1,"total_gas":"10","avg_gas_per_byte":"1","chain_id":"c","tx_proof":"a,b"}"#
+        ).unwrap();
+        assert_eq!(legacy.tx_proofs, vec!["a", "b"]);
+        let legacy: TestRunStats = from_json(
+            br#"{"timestamp":1,"message_count":1,"total_gas":"10","avg_gas_per_byte":"1","chain_id":"c","tx_proof":null}"#
+        ).unwrap();
+        assert!(legacy.tx_proofs.is_empty());
+    }
+
+    #[test]
+    fn query_missing_message() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: "msg_missing".to_string() }).unwrap_err();
+        assert_eq!(err, StdError::generic_err("message not found: msg_missing"));
+    }
+
+    #[test]
+    fn tx_proofs_validation() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        // The old comma separated tx_proof field still parses
+        let msg: ExecuteMsg = from_json(
+            br#"{"record_test_run":{"run_id":"run_1","count":2,"gas":"1000","avg_gas":"10","chain":"test-chain","tx_proof":"tx1,tx2"}}"#
+        ).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let runs: TestRunsResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRuns { start_after: None, limit: None, chain: None, order: None, sort: None, tag: None, node_version: None, include_proofs: None }).unwrap()
+        ).unwrap();
+        assert_eq!(runs.runs[0].tx_count, 2);
+
+        let hash = "ab".repeat(32);
+        let record_with = |run_id: &str, proofs: Vec<String>| {
+            let mut msg = record_run_msg(run_id, 2, 1000, 10, "test-chain");
+            if let ExecuteMsg::RecordTestRun { tx_proofs, .. } = &mut msg {
+                *tx_proofs = Some(proofs);
+            }
+            msg
+        };
+
+        // A blank hash is never accepted
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), record_with("run_2", vec![hash.clone(), " ".to_string()])).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTxProof(_)));
+
+        // With hex_tx_proofs only 64 character hex hashes pass
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::UpdateConfig { max_message_size: None, max_list_limit: None, max_runs_limit: None, max_messages: None, eviction: None, hex_tx_proofs: Some(true), strict_validation: None, regression_threshold_bps: None, min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: None },
+        ).unwrap();
+        for malformed in ["tx1".to_string(), "zz".repeat(32), "ab".repeat(33)] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), record_with("run_2", vec![hash.clone(), malformed])).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidTxProof(_)));
+        }
+        let res = execute(deps.as_mut(), mock_env(), info, record_with("run_2", vec![hash.clone(), hash.to_uppercase()])).unwrap();
+        assert_eq!(res.attributes[4].value, "2");
+    }
+
+    #[test]
+    fn export_runs_csv() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 2, 1000, 10, "test-chain")).unwrap();
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_2", 3, 3000, 20, "odd,\"chain\"")).unwrap();
+
+        let res: ExportRunsCsvResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::ExportRunsCsv { start_after: None, limit: None }).unwrap()
+        ).unwrap();
+        let time = mock_env().block.time.seconds();
+        assert_eq!(res.csv, format!(
+            "run_id,timestamp,chain,count,total_gas,avg_gas_per_byte,tx_count\n\
+             run_1,{time},test-chain,2,1000,10,0\n\
+             run_2,{time},\"odd,\"\"chain\"\"\",3,3000,20,0\n"
+        ));
+        assert_eq!(res.next_start_after, None);
+    }
+
+    #[test]
+    fn strict_validation() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        // Consistent values are accepted
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 10, 1000, 50, "test-chain")).unwrap();
+
+        // avg_gas above the total, or under 1 gas per message, is rejected
+        for msg in [
+            record_run_msg("run_2", 10, 100, 999_999, "test-chain"),
+            record_run_msg("run_2", 10, 5, 1, "test-chain"),
+        ] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidGasValue(_)));
+        }
+
+        // Opting out accepts the same values
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::UpdateConfig { max_message_size: None, max_list_limit: None, max_runs_limit: None, max_messages: None, eviction: None, hex_tx_proofs: None, strict_validation: Some(false), regression_threshold_bps: None, min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: None },
+        ).unwrap();
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_2", 10, 100, 999_999, "test-chain")).unwrap();
+
+        // Configs saved before the flag existed stay strict
+        let legacy: Config = from_json(br#"{"max_message_size":10000,"max_list_limit":30,"max_runs_limit":20}"#).unwrap();
+        assert!(legacy.strict_validation);
+    }
+
+    #[test]
+    fn reset_counters() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 2, 1000, 10, "test-chain")).unwrap();
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone_else", &[]), ExecuteMsg::ResetCounters {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::ResetCounters {}).unwrap();
+        assert_eq!(res.attributes[0].value, "reset_counters");
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.test_count, 0);
+        assert_eq!(config.last_test, Some(env.block.time.seconds()));
+
+        // The run is still there and the contract still reports healthy
+        let runs: TestRunsResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRuns { start_after: None, limit: None, chain: None, order: None, sort: None, tag: None, node_version: None, include_proofs: None }).unwrap()
+        ).unwrap();
+        assert_eq!(runs.runs.len(), 1);
+        assert_eq!(runs.runs[0].id, "run_1");
+
+        let health: HealthResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetHealth {}).unwrap()
+        ).unwrap();
+        assert_eq!(health.status, HealthStatus::Healthy);
+    }
+
+    #[test]
+    fn summary_prefers_reported_bytes() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        // Mixed-size run whose avg_gas would estimate 1000 / 3 = 333 bytes
+        let mut msg = record_run_msg("run_exact", 4, 1000, 3, "test-chain");
+        if let ExecuteMsg::RecordTestRun { bytes, .. } = &mut msg {
+            *bytes = 400;
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        // No reported bytes, falls back to 2000 / 10 = 200
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_estimated", 2, 2000, 10, "test-chain")).unwrap();
+
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        assert_eq!(summary.total_bytes, 600);
+        assert_eq!(summary.gas_per_byte, Uint128::new(5));
+        assert_eq!(summary.avg_bytes_per_msg, 100);
+        assert!(summary.estimated);
+
+        let runs: TestRunsResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRuns { start_after: None, limit: None, chain: None, order: None, sort: None, tag: None, node_version: None, include_proofs: None }).unwrap()
+        ).unwrap();
+        let bytes: Vec<(String, u64)> = runs.runs.into_iter().map(|run| (run.id, run.bytes)).collect();
+        assert_eq!(bytes, vec![("run_exact".to_string(), 400), ("run_estimated".to_string(), 0)]);
+    }
+
+    #[test]
+    fn store_message_gas_used() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let mut ids = vec![];
+        for (height, gas_used) in [(100, Some(Uint128::new(84_000))), (101, None)] {
+            let mut env = mock_env();
+            env.block.height = height;
+            let res = execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: "measured".to_string(), idempotency_key: None, gas_used, dedup: None, compress: None },
+            ).unwrap();
+            ids.push(res.attributes[1].value.clone());
+        }
+
+        let with_gas: MessageResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: ids[0].clone() }).unwrap()
+        ).unwrap();
+        assert_eq!(with_gas.gas_used, Some(Uint128::new(84_000)));
+
+        let without_gas: MessageResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: ids[1].clone() }).unwrap()
+        ).unwrap();
+        assert_eq!(without_gas.gas_used, None);
+
+        // Messages stored before gas_used existed still load
+        let legacy: StoredMessage = from_json(br#"{"content":"old","length":3,"stored_at":1}"#).unwrap();
+        assert_eq!(legacy.gas_used, None);
+    }
+
+    #[test]
+    fn gas_distribution() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let record_with = |run_id: &str, min: u128, median: u128, max: u128| {
+            let mut msg = record_run_msg(run_id, 10, 10_000, 10, "test-chain");
+            if let ExecuteMsg::RecordTestRun { min_gas, max_gas, median_gas, .. } = &mut msg {
+                *min_gas = Some(Uint128::new(min));
+                *median_gas = Some(Uint128::new(median));
+                *max_gas = Some(Uint128::new(max));
+            }
+            msg
+        };
+
+        execute(deps.as_mut(), mock_env(), info.clone(), record_with("run_1", 800, 1000, 1500)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), record_with("run_2", 900, 950, 2500)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_3", 10, 10_000, 10, "test-chain")).unwrap();
+
+        // Out of order or above the total is rejected
+        for msg in [
+            record_with("run_4", 1000, 900, 1500),
+            record_with("run_4", 800, 1000, 900),
+            record_with("run_4", 800, 1000, 20_000),
+        ] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidGasValue(_)));
+        }
+
+        let run: TestRunResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRun { run_id: "run_1".to_string(), include_proofs: None, chain: None }).unwrap()
+        ).unwrap();
+        assert_eq!(run.min_gas, Some(Uint128::new(800)));
+        assert_eq!(run.median_gas, Some(Uint128::new(1000)));
+        assert_eq!(run.max_gas, Some(Uint128::new(1500)));
+
+        let run: TestRunResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRun { run_id: "run_3".to_string(), include_proofs: None, chain: None }).unwrap()
+        ).unwrap();
+        assert_eq!(run.min_gas, None);
+
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::GetTestRun { run_id: "run_missing".to_string(), include_proofs: None, chain: None }).unwrap_err();
+        assert_eq!(err, StdError::not_found("test run run_missing"));
+
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        assert_eq!(summary.min_gas, Some(Uint128::new(800)));
+        assert_eq!(summary.max_gas, Some(Uint128::new(2500)));
+    }
+
+    #[test]
+    fn run_samples() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let table: Vec<GasSample> = [(1000u64, 95_000u128), (10, 80_500), (500, 90_000), (100, 82_000)]
+            .into_iter()
+            .map(|(bytes, gas)| GasSample { bytes, gas: Uint128::new(gas) })
+            .collect();
+        let mut msg = record_run_msg("run_1", 4, 347_500, 216, "test-chain");
+        if let ExecuteMsg::RecordTestRun { samples, .. } = &mut msg {
+            *samples = table.clone();
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Samples come back exactly as submitted
+        let res: RunSamplesResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetRunSamples { run_id: "run_1".to_string(), start_after: None, limit: None, chain: None }).unwrap()
+        ).unwrap();
+        assert_eq!(res.samples, table);
+
+        let res: RunSamplesResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetRunSamples { run_id: "run_1".to_string(), start_after: Some(0), limit: Some(2), chain: None }).unwrap()
+        ).unwrap();
+        assert_eq!(res.samples, table[1..3].to_vec());
+
+        // The sample table is capped
+        let mut msg = record_run_msg("run_2", 4, 347_500, 216, "test-chain");
+        if let ExecuteMsg::RecordTestRun { samples, .. } = &mut msg {
+            *samples = vec![table[0].clone(); MAX_GAS_SAMPLES + 1];
+        }
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::TooManySamples { count: MAX_GAS_SAMPLES as u64 + 1, max: MAX_GAS_SAMPLES as u64 });
+    }
+
+    #[test]
+    fn dashboard() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for run in 0..3 {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(&format!("run_{}", run), 2, 1000, 10, "test-chain")).unwrap();
+        }
+
+        let dashboard: DashboardResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetDashboard { runs_limit: Some(2) }).unwrap()
+        ).unwrap();
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        let runs: TestRunsResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRuns { start_after: None, limit: Some(2), chain: None, order: None, sort: None, tag: None, node_version: None, include_proofs: None }).unwrap()
+        ).unwrap();
+
+        assert_eq!(dashboard, DashboardResponse { config, summary, runs: runs.runs });
+        assert_eq!(dashboard.runs.len(), 2);
+    }
+
+    #[test]
+    fn chain_id_normalization() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for (run_id, chain) in [("run_1", "Osmosis-1"), ("run_2", " osmosis-1 "), ("run_3", "osmosis-1"), ("run_4", "juno-1")] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 2, 1000, 10, chain)).unwrap();
+        }
+
+        let run = find_run(deps.as_ref().storage, "run_1", None).unwrap().unwrap().1;
+        assert_eq!(run.chain_id, "osmosis-1");
+        assert_eq!(run.raw_chain_id, Some("Osmosis-1".to_string()));
+        let run = find_run(deps.as_ref().storage, "run_3", None).unwrap().unwrap().1;
+        assert_eq!(run.raw_chain_id, None);
+
+        // The filter is normalized the same way, so all three spellings aggregate together
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: Some("OSMOSIS-1 ".to_string()), window: None }).unwrap()
+        ).unwrap();
+        assert_eq!(summary.msg_count, 6);
+        assert_eq!(summary.total_gas, Uint128::new(3000));
+
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        assert_eq!(summary.msg_count, 8);
+
+        // A chain id that is only whitespace is still rejected
+        let err = execute(deps.as_mut(), mock_env(), info, record_run_msg("run_5", 2, 1000, 10, "  ")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidChainId(_)));
+    }
+
+    #[test]
+    fn gas_model() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let record_with = |run_id: &str, points: &[(u64, u128)]| {
+            let mut msg = record_run_msg(run_id, 2, 10_000, 10, "test-chain");
+            if let ExecuteMsg::RecordTestRun { samples, .. } = &mut msg {
+                *samples = points.iter().map(|&(bytes, gas)| GasSample { bytes, gas: Uint128::new(gas) }).collect();
+            }
+            msg
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), record_with("run_1", &[(300, 1350), (100, 1100), (400, 1500), (200, 1250)])).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), record_with("run_2", &[(100, 1100), (300, 1350)])).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), record_with("run_3", &[(100, 1000), (100, 1200)])).unwrap();
+        execute(deps.as_mut(), mock_env(), info, record_with("run_4", &[(100, 1000)])).unwrap();
+
+        let model = |run_id: Option<&str>| {
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasModel { run_id: run_id.map(String::from) })
+                .and_then(|bin| from_json::<GasModelResponse>(&bin))
+        };
+
+        // By hand: slope = 260000 / 200000 = 1.3, intercept = 195000000 / 200000 = 975,
+        // r^2 = 260000^2 / (200000 * 340000) = 0.994117...
+        assert_eq!(model(Some("run_1")).unwrap(), GasModelResponse {
+            intercept: Int128::new(975),
+            slope: Int128::new(1_300_000),
+            r_squared: Uint128::new(994_117),
+            scale: Uint128::new(1_000_000),
+            sample_count: 4,
+        });
+
+        // Two points fit exactly
+        let fit = model(Some("run_2")).unwrap();
+        assert_eq!(fit.slope, Int128::new(1_250_000));
+        assert_eq!(fit.intercept, Int128::new(975));
+        assert_eq!(fit.r_squared, Uint128::new(1_000_000));
+
+        // Pooled over every run
+        assert_eq!(model(None).unwrap().sample_count, 9);
+
+        // Equal sizes or a single sample can't be fitted
+        assert!(model(Some("run_3")).is_err());
+        assert!(model(Some("run_4")).is_err());
+        assert!(model(Some("run_missing")).is_err());
+    }
+
+    #[test]
+    fn estimate_gas() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let estimate = |deps: Deps, bytes: u64, chain: Option<&str>| {
+            query(deps, mock_env(), QueryMsg::EstimateGas { bytes, chain: chain.map(String::from) })
+                .and_then(|bin| from_json::<GasEstimateResponse>(&bin))
+        };
+
+        // No runs yet
+        let err = estimate(deps.as_ref(), 3000, None).unwrap_err();
+        assert_eq!(err, StdError::generic_err("No data available"));
+
+        // A single run without samples uses its avg_gas_per_byte
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 2, 100_000, 30, "juno-1")).unwrap();
+        assert_eq!(estimate(deps.as_ref(), 3000, Some("juno-1")).unwrap(), GasEstimateResponse {
+            estimated_gas: Uint128::new(90_000),
+            model: "avg_gas_per_byte".to_string(),
+            runs_used: 1,
+        });
+
+        // Sampled runs on another chain are fitted and don't mix with juno-1
+        for (run_id, points) in [("run_2", [(100u64, 1100u128), (300, 1350)]), ("run_3", [(200, 1250), (400, 1500)])] {
+            let mut msg = record_run_msg(run_id, 2, 10_000, 10, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { samples, .. } = &mut msg {
+                *samples = points.iter().map(|&(bytes, gas)| GasSample { bytes, gas: Uint128::new(gas) }).collect();
+            }
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        // 975 + 1.3 * 3000
+        assert_eq!(estimate(deps.as_ref(), 3000, Some("osmosis-1")).unwrap(), GasEstimateResponse {
+            estimated_gas: Uint128::new(4875),
+            model: "linear_fit".to_string(),
+            runs_used: 2,
+        });
+        assert_eq!(estimate(deps.as_ref(), 3000, Some("juno-1")).unwrap().model, "avg_gas_per_byte");
+
+        // Unknown chain has no data
+        assert!(estimate(deps.as_ref(), 3000, Some("stargaze-1")).is_err());
+
+        // Stored rates too large to sum are an error rather than a panic
+        let mut run = TEST_RUNS.load(deps.as_ref().storage, ("juno-1", "run_1")).unwrap();
+        run.avg_gas_per_byte = Uint128::MAX;
+        TEST_RUNS.save(deps.as_mut().storage, ("juno-1", "run_1"), &run).unwrap();
+        TEST_RUNS.save(deps.as_mut().storage, ("juno-1", "run_4"), &run).unwrap();
+        let err = estimate(deps.as_ref(), 3000, Some("juno-1")).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+    }
+
+    #[test]
+    fn is_owner() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+
+        let is_owner = |address: &str| {
+            query(deps.as_ref(), mock_env(), QueryMsg::IsOwner { address: address.to_string() })
+                .and_then(|bin| from_json::<IsOwnerResponse>(&bin))
+        };
+        assert!(is_owner("creator").unwrap().is_owner);
+        assert!(!is_owner("someone_else").unwrap().is_owner);
+        assert!(is_owner("").is_err());
+    }
+
+    #[test]
+    fn created_at_height() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
+        ).unwrap();
+        assert_eq!(config.created_at_height, mock_env().block.height);
+    }
+
+    #[test]
+    fn estimate_fee() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let priced = |run_id: &str, avg_gas: u128, price: Option<&str>, denom: Option<&str>| {
+            let mut msg = record_run_msg(run_id, 2, 100_000, avg_gas, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { gas_price, fee_denom, .. } = &mut msg {
+                *gas_price = price.map(|price| price.parse().unwrap());
+                *fee_denom = denom.map(String::from);
+            }
+            msg
+        };
+
+        // Price and denom go together
+        for msg in [priced("run_x", 30, Some("0.025"), None), priced("run_x", 30, None, Some("uosmo")), priced("run_x", 30, Some("0.025"), Some(" "))] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidGasPrice(_)));
+        }
+
+        // No price recorded yet
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_0", 2, 100_000, 30, "osmosis-1")).unwrap();
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::EstimateFee { bytes: 3000, chain: Some("osmosis-1".to_string()) }).unwrap_err();
+        assert_eq!(err, StdError::generic_err("No data available"));
+
+        // The later run's price is used: 3000 bytes * 30 gas = 90000 gas
+        let mut env = mock_env();
+        execute(deps.as_mut(), env.clone(), info.clone(), priced("run_1", 30, Some("0.1"), Some("uosmo"))).unwrap();
+        env.block.time = env.block.time.plus_seconds(60);
+        execute(deps.as_mut(), env, info.clone(), priced("run_2", 30, Some("0.025"), Some("uosmo"))).unwrap();
+
+        let fee: Coin = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::EstimateFee { bytes: 3000, chain: Some("osmosis-1".to_string()) }).unwrap()
+        ).unwrap();
+        assert_eq!(fee, Coin { denom: "uosmo".to_string(), amount: Uint128::new(2250) });
+
+        // Fractional fees round up: 7 bytes * 30 gas * 0.025 = 5.25
+        let fee: Coin = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::EstimateFee { bytes: 7, chain: Some("osmosis-1".to_string()) }).unwrap()
+        ).unwrap();
+        assert_eq!(fee.amount, Uint128::new(6));
+    }
+
+    #[test]
+    fn summary_message_length_range() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        assert_eq!((summary.min_msg_length, summary.max_msg_length), (0, 0));
+
+        for (height, length) in [(100, 5), (101, 50)] {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(
+                deps.as_mut(),
+                env,
+                info.clone(),
+                ExecuteMsg::StoreMessage { content: "x".repeat(length), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ).unwrap();
+        }
+
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        assert_eq!((summary.min_msg_length, summary.max_msg_length), (5, 50));
+    }
+
+    #[test]
+    fn gas_summary_by_chain() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for (run_id, count, gas, avg_gas, chain) in [
+            ("run_1", 2, 10_000u128, 10u128, "osmosis-1"),
+            ("run_2", 3, 20_000, 20, "Osmosis-1"),
+            ("run_3", 4, 8_000, 4, "juno-1"),
+        ] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, count, gas, avg_gas, chain)).unwrap();
+        }
+
+        let res: ChainGasSummaryResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummaryByChain {}).unwrap()
+        ).unwrap();
+        assert_eq!(res.chains, vec![
+            ChainGasSummary { chain_id: "juno-1".to_string(), run_count: 1, msg_count: 4, total_gas: Uint128::new(8_000), gas_per_byte: Uint128::new(4) },
+            // 30000 gas over 1000 + 1000 estimated bytes
+            ChainGasSummary { chain_id: "osmosis-1".to_string(), run_count: 2, msg_count: 5, total_gas: Uint128::new(30_000), gas_per_byte: Uint128::new(15) },
+        ]);
+
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: Some("juno-1".to_string()), window: None }).unwrap()
+        ).unwrap();
+        assert_eq!(summary.msg_count, 4);
+        assert_eq!(summary.total_gas, Uint128::new(8_000));
+        assert_eq!(summary.gas_per_byte, Uint128::new(4));
+    }
+
+    #[test]
+    fn tx_hashes_list() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let mut msg = record_run_msg("run_1", 3, 1000, 10, "test-chain");
+        if let ExecuteMsg::RecordTestRun { tx_hashes, .. } = &mut msg {
+            *tx_hashes = Some(vec!["hash1".to_string(), "hash2".to_string(), "hash3".to_string()]);
+        }
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(res.attributes[4].value, "3");
+
+        // tx_hashes wins over the old tx_proof string
+        let msg: ExecuteMsg = from_json(
+            br#"{"record_test_run":{"run_id":"run_2","count":2,"gas":"1000","avg_gas":"10","chain":"test-chain","tx_proof":"a,b,c,d","tx_hashes":["e","f"]}}"#
+        ).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let run: TestRunResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRun { run_id: "run_2".to_string(), include_proofs: None, chain: None }).unwrap()
+        ).unwrap();
+        assert_eq!(run.tx_count, 2);
+
+        // Entries must be non-empty
+        let mut msg = record_run_msg("run_3", 3, 1000, 10, "test-chain");
+        if let ExecuteMsg::RecordTestRun { tx_hashes, .. } = &mut msg {
+            *tx_hashes = Some(vec!["hash1".to_string(), "".to_string()]);
+        }
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTxProof(_)));
+    }
+
+    #[test]
+    fn top_runs_by_gas() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        for (run_id, gas) in [("run_a", 2_000u128), ("run_b", 9_000), ("run_c", 5_000)] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 2, gas, 10, "test-chain")).unwrap();
+        }
+
+        let top = |limit: Option<u32>| -> Vec<String> {
+            let res: TestRunsResponse = from_json(
+                query(deps.as_ref(), mock_env(), QueryMsg::TopRunsByGas { limit }).unwrap()
+            ).unwrap();
+            res.runs.into_iter().map(|run| run.id).collect()
+        };
+        assert_eq!(top(None), vec!["run_b", "run_c", "run_a"]);
+        assert_eq!(top(Some(2)), vec!["run_b", "run_c"]);
+    }
+
+    #[test]
+    fn gas_summary_aggregates_match_full_scan() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        // A window covering every run makes the cached summary's spread match the full scan
+        let assert_in_sync = |deps: Deps| {
+            let window = Some(DEFAULT_MAX_LIST_LIMIT);
+            let cached: GasSummary =
+                from_json(query(deps, mock_env(), QueryMsg::GetGasSummary { chain: None, window }).unwrap()).unwrap();
+            let exact: GasSummary =
+                from_json(query(deps, mock_env(), QueryMsg::GetGasSummaryExact {}).unwrap()).unwrap();
+            assert_eq!(cached, exact);
+            cached
+        };
+        
+        let record = |deps: DepsMut, run_id: &str, gas: u128, min_gas: u128, max_gas: u128| {
+            let mut msg = record_run_msg(run_id, 2, gas, gas / 100, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { min_gas: min, max_gas: max, .. } = &mut msg {
+                *min = Some(Uint128::new(min_gas));
+                *max = Some(Uint128::new(max_gas));
+            }
+            execute(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        };
+        
+        // Ids come from the block height, the first store at a height is overwritten
+        for (height, content) in [(1, "overwritten"), (1, "short"), (2, "a longer message"), (3, "mid-size")] {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::StoreMessage { content: content.to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None };
+            execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        }
+        record(deps.as_mut(), "run1", 40_000, 15_000, 25_000);
+        record(deps.as_mut(), "run2", 60_000, 20_000, 40_000);
+        record(deps.as_mut(), "run3", 80_000, 10_000, 70_000);
+        
+        let summary = assert_in_sync(deps.as_ref());
+        assert_eq!(summary.total_gas, Uint128::new(180_000));
+        assert_eq!(summary.min_gas, Some(Uint128::new(10_000)));
+        assert_eq!(summary.max_gas, Some(Uint128::new(70_000)));
+        assert_eq!((summary.min_msg_length, summary.max_msg_length), (5, 16));
+        assert!(!summary.gas_std_dev.is_zero());
+        
+        // Overwrites, deletes and clears all move the aggregates
+        let mut msg = record_run_msg("run3", 2, 50_000, 500, "osmosis-1");
+        if let ExecuteMsg::RecordTestRun { overwrite, .. } = &mut msg {
+            *overwrite = Some(true);
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::DeleteRange { start_after: None, limit: 1 }).unwrap();
+        
+        let summary = assert_in_sync(deps.as_ref());
+        assert_eq!(summary.total_gas, Uint128::new(150_000));
+        assert_eq!(summary.min_gas, Some(Uint128::new(15_000)));
+        assert_eq!(summary.max_gas, Some(Uint128::new(40_000)));
+        assert_eq!((summary.min_msg_length, summary.max_msg_length), (8, 16));
+        
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClearData { limit: None }).unwrap();
+        let summary = assert_in_sync(deps.as_ref());
+        assert!(summary.total_gas.is_zero());
+        assert_eq!((summary.min_gas, summary.max_msg_length), (None, 0));
+        
+        record(deps.as_mut(), "run4", 30_000, 12_000, 18_000);
+        let summary = assert_in_sync(deps.as_ref());
+        assert_eq!(summary.total_gas, Uint128::new(30_000));
+        assert_eq!(summary.min_gas, Some(Uint128::new(12_000)));
+    }
+
+    #[test]
+    fn compare_runs_flags_regressions() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let mut env = mock_env();
+        for (run_id, count, gas, avg_gas, chain) in [
+            ("v1", 10, 100_000, 100, "osmosis-1"),
+            ("v2", 10, 104_000, 104, "osmosis-1"),
+            ("v3", 20, 220_000, 110, "osmosis-1"),
+            ("other", 10, 500_000, 500, "juno-1"),
+            ("empty", 0, 0, 0, "osmosis-1"),
+        ] {
+            env.block.time = env.block.time.plus_seconds(10);
+            execute(deps.as_mut(), env.clone(), info.clone(), record_run_msg(run_id, count, gas, avg_gas, chain)).unwrap();
+        }
+        
+        let compare = |deps: Deps, base: &str, against: &str| -> StdResult<RunComparisonResponse> {
+            let msg = QueryMsg::CompareRuns { base: base.to_string(), against: against.to_string(), chain: None };
+            query(deps, mock_env(), msg).map(|bin| from_json(bin).unwrap())
+        };
+        
+        // 4% is under the default 5% threshold
+        let res = compare(deps.as_ref(), "v1", "v2").unwrap();
+        assert_eq!(res.avg_gas_per_byte.delta, Int128::new(4));
+        assert_eq!(res.avg_gas_per_byte.change_bps, Int128::new(400));
+        assert_eq!(res.gas_per_message.base, Uint128::new(10_000));
+        assert_eq!(res.gas_per_message.against, Uint128::new(10_400));
+        assert_eq!(res.threshold_bps, DEFAULT_REGRESSION_THRESHOLD_BPS);
+        assert!(!res.regression);
+        
+        let res = compare(deps.as_ref(), "v1", "v3").unwrap();
+        assert_eq!(res.gas_per_message.change_bps, Int128::new(1000));
+        assert_eq!(res.message_count.delta, Int128::new(10));
+        assert!(res.regression);
+        
+        // Improvements are negative and never a regression
+        let res = compare(deps.as_ref(), "v3", "v1").unwrap();
+        assert_eq!(res.avg_gas_per_byte.delta, Int128::new(-10));
+        assert!(!res.regression);
+        
+        let err = compare(deps.as_ref(), "v1", "missing").unwrap_err();
+        assert!(err.to_string().contains("test run not found: missing"));
+        let err = compare(deps.as_ref(), "empty", "v1").unwrap_err();
+        assert!(err.to_string().contains("test run has no messages: empty"));
+        
+        // A lower threshold flags the 4% increase
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::UpdateConfig { max_message_size: None, max_list_limit: None, max_runs_limit: None, max_messages: None, eviction: None, hex_tx_proofs: None, strict_validation: None, regression_threshold_bps: Some(300), min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: None },
+        ).unwrap();
+        assert!(compare(deps.as_ref(), "v1", "v2").unwrap().regression);
+        
+        // Latest delta skips other chains and runs without messages
+        let res: RunComparisonResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetLatestDelta { chain: "Osmosis-1".to_string() }).unwrap()
+        ).unwrap();
+        assert_eq!((res.base.as_str(), res.against.as_str()), ("v2", "v3"));
+        assert!(res.regression);
+        
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::GetLatestDelta { chain: "juno-1".to_string() }).unwrap_err();
+        assert!(err.to_string().contains("need at least two runs on chain: juno-1"));
+    }
+
+    #[test]
+    fn lifetime_store_count_survives_clear() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let store = |deps: DepsMut, height: u64| {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::StoreMessage { content: "payload".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None };
+            execute(deps, env, mock_info("creator", &[]), msg).unwrap();
+        };
+        let config = |deps: Deps| -> ConfigResponse {
+            from_json(query(deps, mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap()
+        };
+        
+        store(deps.as_mut(), 1);
+        store(deps.as_mut(), 2);
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run1", 2, 1000, 10, "osmosis-1")).unwrap();
+        let before = config(deps.as_ref());
+        assert_eq!((before.lifetime_store_count, before.test_count), (2, 1));
+        
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClearData { limit: None }).unwrap();
+        store(deps.as_mut(), 3);
+        
+        let after = config(deps.as_ref());
+        assert_eq!(after.lifetime_store_count, 3);
+        assert_eq!((after.test_count, after.message_count), (0, 1));
+    }
+
+    #[test]
+    fn dedup_store() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let store = |deps: DepsMut, height: u64, content: &str, dedup: Option<bool>| {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::StoreMessage { content: content.to_string(), idempotency_key: None, gas_used: None, dedup, compress: None };
+            execute(deps, env, mock_info("creator", &[]), msg).unwrap()
+        };
+        let message_count = |deps: Deps| MESSAGES.keys(deps.storage, None, None, cosmwasm_std::Order::Ascending).count();
+        
+        let res = store(deps.as_mut(), 1, "same payload", Some(true));
+        assert_eq!(res.attributes[1].value, "msg_1");
+        assert_eq!(res.attributes.last().unwrap().value, "false");
+        
+        let res = store(deps.as_mut(), 2, "same payload", Some(true));
+        assert_eq!(res.attributes[1].value, "msg_1");
+        assert_eq!(res.attributes[2].key, "already_exists");
+        assert_eq!(res.attributes[2].value, "true");
+        assert_eq!(message_count(deps.as_ref()), 1);
+        
+        // Without dedup the same content is written again
+        store(deps.as_mut(), 3, "same payload", None);
+        assert_eq!(message_count(deps.as_ref()), 2);
+        
+        // Once the original is overwritten its hash no longer matches
+        store(deps.as_mut(), 1, "replaced", None);
+        let res = store(deps.as_mut(), 4, "same payload", Some(true));
+        assert_eq!(res.attributes[1].value, "msg_4");
+        assert_eq!(message_count(deps.as_ref()), 3);
+    }
+
+    #[test]
+    fn get_test_run_with_proofs() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let mut msg = record_run_msg("run_1", 2, 2000, 10, "osmosis-1");
+        if let ExecuteMsg::RecordTestRun { tx_hashes, .. } = &mut msg {
+            *tx_hashes = Some(vec!["ABC123".to_string(), "DEF456".to_string()]);
+        }
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        
+        let get = |include_proofs: Option<bool>| -> TestRunResponse {
+            let msg = QueryMsg::GetTestRun { run_id: "run_1".to_string(), include_proofs, chain: None };
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+        
+        let run = get(None);
+        assert_eq!((run.id.as_str(), run.count, run.tx_count), ("run_1", 2, 2));
+        assert_eq!(run.tx_proofs, None);
+        assert_eq!(get(Some(true)).tx_proofs, Some(vec!["ABC123".to_string(), "DEF456".to_string()]));
+        
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::GetTestRun { run_id: "nope".to_string(), include_proofs: Some(true), chain: None }).unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }));
+    }
+
+    #[test]
+    fn delete_test_run() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("good", 10, 10_000, 10, "osmosis-1")).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("typo", 10, 50_000, 50, "osmosis-l")).unwrap();
+        
+        let delete = ExecuteMsg::DeleteTestRun { run_id: "typo".to_string(), chain: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone_else", &[]), delete.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), delete.clone()).unwrap();
+        assert_eq!(res.attributes[2].value, "osmosis-l");
+        assert_eq!(res.attributes[3].value, mock_env().block.time.seconds().to_string());
+        
+        let config: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(config.test_count, 1);
+        let summary: GasSummary = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()
+        ).unwrap();
+        assert_eq!(summary.total_gas, Uint128::new(10_000));
+        assert_eq!(summary.msg_count, 10);
+        
+        let err = execute(deps.as_mut(), mock_env(), info, delete).unwrap_err();
+        assert_eq!(err, ContractError::RunNotFound { run_id: "typo".to_string() });
+    }
+
+    #[test]
+    fn list_chains() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        for (run_id, chain) in [("run_1", "osmosis-1"), ("run_2", "juno-1"), ("run_3", "Osmosis-1")] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 10, 10_000, 10, chain)).unwrap();
+        }
+        
+        let res: ChainsResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::ListChains {}).unwrap()).unwrap();
+        assert_eq!(res.chains, vec!["juno-1".to_string(), "osmosis-1".to_string()]);
+    }
+
+    #[test]
+    fn archive_message() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        for height in 1..4 {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::StoreMessage { content: "payload".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None };
+            execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        }
+        
+        let archive = ExecuteMsg::ArchiveMessage { id: "msg_2".to_string(), archived: true };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone_else", &[]), archive.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), info.clone(), archive).unwrap();
+        
+        let list = |deps: Deps, include_archived: Option<bool>| -> Vec<String> {
+            let msg = QueryMsg::ListMessages { start_after: None, end_before: None, limit: Some(2), include_archived };
+            let res: ListMessagesResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.msgs.into_iter().map(|m| m.id).collect()
+        };
+        
+        // The limit counts only visible messages
+        assert_eq!(list(deps.as_ref(), None), vec!["msg_1", "msg_3"]);
+        assert_eq!(list(deps.as_ref(), Some(true)), vec!["msg_1", "msg_2"]);
+        
+        // Still readable directly, and restorable
+        let message: MessageResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: "msg_2".to_string() }).unwrap()
+        ).unwrap();
+        assert!(message.archived);
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ArchiveMessage { id: "msg_2".to_string(), archived: false }).unwrap();
+        assert_eq!(list(deps.as_ref(), None), vec!["msg_1", "msg_2"]);
+        
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ArchiveMessage { id: "msg_9".to_string(), archived: true }).unwrap_err();
+        assert_eq!(err, ContractError::MessageNotFound { id: "msg_9".to_string() });
+    }
+
+    #[test]
+    fn test_runs_by_chain() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        for (run_id, chain) in [
+            ("run_1", "osmosis-1"),
+            ("run_2", "juno-1"),
+            ("run_3", "osmosis-1"),
+            ("run_4", "juno-1"),
+            ("run_5", "Osmosis-1"),
+            ("run_6", "osmosis-1"),
+        ] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 10, 10_000, 10, chain)).unwrap();
+        }
+        let page = |deps: Deps, start_after: Option<&str>, limit: u32| -> Vec<String> {
+            let msg = QueryMsg::GetTestRuns {
+                start_after: start_after.map(str::to_string),
+                limit: Some(limit),
+                chain: Some("OSMOSIS-1".to_string()),
+                order: None,
+                sort: None,
+                tag: None,
+                node_version: None,
+                include_proofs: None,
+            };
+            let res: TestRunsResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.runs.into_iter().map(|run| run.id).collect()
+        };
+        
+        // The limit applies to matching runs, start_after keeps working as a key cursor
+        assert_eq!(page(deps.as_ref(), None, 2), vec!["run_6", "run_5"]);
+        assert_eq!(page(deps.as_ref(), None, 10), vec!["run_6", "run_5", "run_3", "run_1"]);
+        assert_eq!(page(deps.as_ref(), Some("run_5"), 10), vec!["run_3", "run_1"]);
+        
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::DeleteTestRun { run_id: "run_3".to_string(), chain: None }).unwrap();
+        assert_eq!(page(deps.as_ref(), None, 10), vec!["run_6", "run_5", "run_1"]);
+    }
+
+    #[test]
+    fn gas_per_byte_bounds() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let bounds = |min: u128, max: u128| ExecuteMsg::UpdateConfig {
+            max_message_size: None,
+            max_list_limit: None,
+            max_runs_limit: None,
+            max_messages: None,
+            eviction: None,
+            hex_tx_proofs: None,
+            strict_validation: None,
+            regression_threshold_bps: None,
+            min_gas_per_byte: Some(Uint128::new(min)),
+            max_gas_per_byte: Some(Uint128::new(max)),
+            avg_gas_tolerance_bps: None,
+            open_recording: None,
+            max_proof_bytes: None,
+            gas_budget: None,
+        };
+        
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), bounds(100, 10)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidConfig(_)));
+        execute(deps.as_mut(), mock_env(), info.clone(), bounds(5, 50)).unwrap();
+        
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 10, 10_000, 20, "osmosis-1")).unwrap();
+        for (run_id, avg_gas) in [("run_2", 2), ("run_3", 2000)] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 10, 10_000, avg_gas, "osmosis-1")).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidGasValue(_)));
+        }
+        
+        // Zero clears a bound
+        execute(deps.as_mut(), mock_env(), info.clone(), bounds(5, 0)).unwrap();
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_3", 10, 10_000, 2000, "osmosis-1")).unwrap();
+    }
+
+    #[test]
+    fn test_runs_pagination_both_orders() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let ids: Vec<String> = (1..=7).map(|i| format!("run_{}", i)).collect();
+        for id in &ids {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(id, 10, 10_000, 10, "osmosis-1")).unwrap();
+        }
+        
+        let walk = |deps: Deps, order: Option<&str>| -> Vec<String> {
+            let mut seen = vec![];
+            let mut start_after = None;
+            loop {
+                let msg = QueryMsg::GetTestRuns { start_after, limit: Some(3), chain: None, order: order.map(str::to_string), sort: None, tag: None, node_version: None, include_proofs: None };
+                let res: TestRunsResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+                seen.extend(res.runs.into_iter().map(|run| run.id));
+                match res.next_start_after {
+                    Some(next) => start_after = Some(next),
+                    None => break,
+                }
+                assert!(seen.len() <= ids.len(), "pagination did not terminate");
+            }
+            seen
+        };
+        
+        assert_eq!(walk(deps.as_ref(), Some("asc")), ids);
+        let mut descending = ids.clone();
+        descending.reverse();
+        assert_eq!(walk(deps.as_ref(), None), descending);
+        assert_eq!(walk(deps.as_ref(), Some("desc")), descending);
+        
+        let msg = QueryMsg::GetTestRuns { start_after: None, limit: None, chain: None, order: Some("sideways".to_string()), sort: None, tag: None, node_version: None, include_proofs: None };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+    }
+
+    #[test]
+    fn run_tags_and_note() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let tagged = |run_id: &str, run_tags: &[&str], run_note: Option<String>| {
+            let mut msg = record_run_msg(run_id, 10, 10_000, 10, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { tags, note, .. } = &mut msg {
+                *tags = Some(run_tags.iter().map(|tag| tag.to_string()).collect());
+                *note = run_note;
+            }
+            msg
+        };
+        
+        execute(deps.as_mut(), mock_env(), info.clone(), tagged("run_1", &["wasmd v0.50", "pre-upgrade"], Some("baseline".to_string()))).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), tagged("run_2", &["wasmd v0.51"], None)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), tagged("run_3", &["wasmd v0.50"], None)).unwrap();
+        
+        let run: TestRunResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRun { run_id: "run_1".to_string(), include_proofs: None, chain: None }).unwrap()
+        ).unwrap();
+        assert_eq!(run.tags, vec!["wasmd v0.50", "pre-upgrade"]);
+        assert_eq!(run.note.as_deref(), Some("baseline"));
+        
+        let msg = QueryMsg::GetTestRuns { start_after: None, limit: Some(1), chain: None, order: None, sort: None, tag: Some("wasmd v0.50".to_string()), node_version: None, include_proofs: None };
+        let res: TestRunsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.runs.len(), 1);
+        assert_eq!(res.runs[0].id, "run_3");
+        let msg = QueryMsg::GetTestRuns { start_after: res.next_start_after, limit: Some(1), chain: None, order: None, sort: None, tag: Some("wasmd v0.50".to_string()), node_version: None, include_proofs: None };
+        let res: TestRunsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.runs[0].id, "run_1");
+        
+        // Errors name the field that was too long
+        let long_tag = "x".repeat(MAX_RUN_TAG_LENGTH + 1);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), tagged("run_4", &["ok", &long_tag], None)).unwrap_err();
+        assert!(err.to_string().contains("tag 1"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), tagged("run_4", &["ok"; MAX_RUN_TAGS + 1], None)).unwrap_err();
+        assert!(err.to_string().contains("tags exceeds maximum"));
+        let err = execute(deps.as_mut(), mock_env(), info, tagged("run_4", &[], Some("n".repeat(MAX_RUN_NOTE_LENGTH + 1)))).unwrap_err();
+        assert!(err.to_string().contains("note is"));
+        
+        // Runs stored before tags existed still load
+        let legacy: TestRunStats = from_json(
+            br#"{"timestamp":1,"message_count":1,"total_gas":"100","avg_gas_per_byte":"1","chain_id":"osmosis-1"}"#
+        ).unwrap();
+        assert!(legacy.tags.is_empty());
+        assert_eq!(legacy.note, None);
+    }
+
+    #[test]
+    fn compressed_store_round_trip() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let store = |deps: DepsMut, height: u64, content: &str, compress: Option<bool>| {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::StoreMessage { content: content.to_string(), idempotency_key: None, gas_used: None, dedup: None, compress };
+            execute(deps, env, mock_info("creator", &[]), msg).unwrap();
+        };
+        let payload = format!("{}é{}", "a".repeat(1000), "b".repeat(1000));
+        store(deps.as_mut(), 1, &payload, Some(true));
+        
+        let stored = MESSAGES.load(deps.as_ref().storage, "msg_1").unwrap();
+        assert!(stored.compressed);
+        assert!(stored.content.len() < 100);
+        
+        let message: MessageResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: "msg_1".to_string() }).unwrap()
+        ).unwrap();
+        assert_eq!(message.content, payload);
+        assert_eq!(message.length, payload.len() as u64);
+        
+        // Content that wouldn't shrink is kept as is
+        store(deps.as_mut(), 2, "no runs here", Some(true));
+        let stored = MESSAGES.load(deps.as_ref().storage, "msg_2").unwrap();
+        assert!(!stored.compressed);
+        assert_eq!(stored.content, "no runs here");
+        
+        assert_eq!(rle_decode(&rle_encode(payload.as_bytes())).unwrap(), payload.as_bytes());
+        assert!(rle_decode(&[3]).is_err());
+    }
+
+    #[test]
+    fn append_to_message() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let msg = ExecuteMsg::StoreMessage { content: "abc".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let id = format!("msg_{}", mock_env().block.height);
+        
+        let append = |suffix: String| ExecuteMsg::AppendToMessage { id: id.clone(), suffix };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone_else", &[]), append("def".to_string())).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), append("def".to_string())).unwrap();
+        assert_eq!(res.attributes[3].value, "6");
+        
+        let message: MessageResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: id.clone() }).unwrap()
+        ).unwrap();
+        assert_eq!(message.content, "abcdef");
+        assert_eq!(message.length, 6);
+        assert_eq!(message.time, env.block.time.seconds());
+        
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), append("x".repeat(MAX_MESSAGE_SIZE as usize))).unwrap_err();
+        assert_eq!(err, ContractError::MessageTooLarge { size: MAX_MESSAGE_SIZE + 6, max: MAX_MESSAGE_SIZE });
+        
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::AppendToMessage { id: "msg_missing".to_string(), suffix: "x".to_string() },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::NoData {});
+    }
+
+    #[test]
+    fn run_ids_scoped_by_chain() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        // The same id on two chains are separate runs
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 10, 10_000, 10, "osmosis-1")).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 10, 20_000, 20, "juno-1")).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_2", 10, 10_000, 10, "juno-1")).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 10, 10_000, 10, "Juno-1")).unwrap_err();
+        assert_eq!(err, ContractError::RunExists { run_id: "run_1".to_string() });
+        
+        let get = |deps: Deps, chain: Option<&str>| {
+            let msg = QueryMsg::GetTestRun { run_id: "run_1".to_string(), include_proofs: None, chain: chain.map(str::to_string) };
+            query(deps, mock_env(), msg).map(|res| from_json::<TestRunResponse>(res).unwrap())
+        };
+        let err = get(deps.as_ref(), None).unwrap_err();
+        assert_eq!(err, StdError::generic_err("test run run_1 exists on chains juno-1, osmosis-1, specify a chain"));
+        assert_eq!(get(deps.as_ref(), Some("JUNO-1")).unwrap().gas, Uint128::new(20_000));
+        
+        // Unfiltered listings return both runs under the shared id
+        let msg = QueryMsg::GetTestRuns { start_after: None, limit: Some(1), chain: None, order: Some("asc".to_string()), sort: None, tag: None, node_version: None, include_proofs: None };
+        let res: TestRunsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let chains: Vec<String> = res.runs.into_iter().map(|run| run.chain).collect();
+        assert_eq!(chains, vec!["juno-1", "osmosis-1"]);
+        assert_eq!(res.next_start_after, Some("run_1".to_string()));
+        
+        let msg = QueryMsg::GetChainRuns { chain: "juno-1".to_string(), start_after: None, limit: None };
+        let res: TestRunsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let ids: Vec<String> = res.runs.into_iter().map(|run| run.id).collect();
+        assert_eq!(ids, vec!["run_1", "run_2"]);
+        
+        let delete = ExecuteMsg::DeleteTestRun { run_id: "run_1".to_string(), chain: None };
+        assert!(execute(deps.as_mut(), mock_env(), info.clone(), delete).is_err());
+        let delete = ExecuteMsg::DeleteTestRun { run_id: "run_1".to_string(), chain: Some("juno-1".to_string()) };
+        execute(deps.as_mut(), mock_env(), info, delete).unwrap();
+        assert_eq!(get(deps.as_ref(), None).unwrap().chain, "osmosis-1");
+    }
+
+    #[test]
+    fn migrate_moves_legacy_runs() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        for run_id in ["run_1", "run_2", "run_3"] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 10, 10_000, 10, "osmosis-1")).unwrap();
+        }
+        
+        // Rewrite the runs under their old bare keys
+        for run_id in ["run_1", "run_2", "run_3"] {
+            let (_, mut run) = find_run(deps.as_ref().storage, run_id, None).unwrap().unwrap();
+            TEST_RUNS.remove(deps.as_mut().storage, ("osmosis-1", run_id));
+            RUN_CHAINS.remove(deps.as_mut().storage, run_id);
+            run.chain_id = "Osmosis-1".to_string();
+            LEGACY_TEST_RUNS.save(deps.as_mut().storage, run_id, &run).unwrap();
+        }
+        
+        let attribute = |res: &Response, key: &str| res.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+        assert_eq!((attribute(&res, "runs_migrated"), attribute(&res, "legacy_remaining")), ("2".to_string(), "1".to_string()));
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(0) }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMigration(_)));
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+        assert_eq!((attribute(&res, "runs_migrated"), attribute(&res, "legacy_remaining")), ("1".to_string(), "0".to_string()));
+        assert!(LEGACY_TEST_RUNS.is_empty(deps.as_ref().storage));
+        let (chain, run) = find_run(deps.as_ref().storage, "run_3", None).unwrap().unwrap();
+        assert_eq!(chain, "osmosis-1");
+        assert_eq!(run.total_gas, Uint128::new(10_000));
+    }
+
+    #[test]
+    fn migrate_version_guard() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), InstantiateMsg::default()).unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+        
+        set_contract_version(deps.as_mut().storage, "crates.io:other-contract", CONTRACT_VERSION).unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+        assert!(err.to_string().contains("cannot migrate crates.io:other-contract"));
+        
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "999.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+        assert!(err.to_string().contains("cannot downgrade from 999.0.0"));
+        
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1-beta").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+        assert_eq!(get_contract_version(deps.as_ref().storage).unwrap().version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn liveness() {
+        let mut deps = mock_dependencies();
+        let res: LivenessResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Health {}).unwrap()).unwrap();
+        assert!(!res.ok);
+        
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        let res: LivenessResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Health {}).unwrap()).unwrap();
+        assert_eq!(res, LivenessResponse { ok: true, paused: false, message_count: 0 });
+        
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::SetPaused { paused: true }).unwrap();
+        let res: LivenessResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Health {}).unwrap()).unwrap();
+        assert!(res.ok && res.paused);
+    }
+
+    #[test]
+    fn latest_run() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let latest = |deps: Deps, chain: Option<&str>| -> StdResult<String> {
+            let msg = QueryMsg::GetLatestRun { chain: chain.map(str::to_string) };
+            query(deps, mock_env(), msg).map(|res| from_json::<TestRunResponse>(res).unwrap().id)
+        };
+        let err = latest(deps.as_ref(), None).unwrap_err();
+        assert_eq!(err, StdError::generic_err(ContractError::NoData {}.to_string()));
+        
+        for (run_id, chain, timestamp) in [
+            ("run_1", "osmosis-1", 300),
+            ("run_2", "osmosis-1", 100),
+            ("run_3", "juno-1", 200),
+            ("run_4", "juno-1", 200),
+        ] {
+            let mut env = mock_env();
+            env.block.time = cosmwasm_std::Timestamp::from_seconds(timestamp);
+            execute(deps.as_mut(), env, info.clone(), record_run_msg(run_id, 10, 10_000, 10, chain)).unwrap();
+        }
+        
+        assert_eq!(latest(deps.as_ref(), None).unwrap(), "run_1");
+        // Equal timestamps go to the greater run id
+        assert_eq!(latest(deps.as_ref(), Some("Juno-1")).unwrap(), "run_4");
+        assert!(latest(deps.as_ref(), Some("stargaze-1")).is_err());
+        
+        // Removing the latest run falls back to the next newest
+        let delete = ExecuteMsg::DeleteTestRun { run_id: "run_1".to_string(), chain: None };
+        execute(deps.as_mut(), mock_env(), info, delete).unwrap();
+        assert_eq!(latest(deps.as_ref(), Some("osmosis-1")).unwrap(), "run_2");
+        assert_eq!(latest(deps.as_ref(), None).unwrap(), "run_4");
+    }
+
+    #[test]
+    fn derived_avg_gas() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let run_msg = |run_id: &str, avg: Option<u128>, run_bytes: u64| {
+            let mut msg = record_run_msg(run_id, 4, 10_000, 0, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { avg_gas, bytes, .. } = &mut msg {
+                *avg_gas = avg.map(Uint128::new);
+                *bytes = run_bytes;
+            }
+            msg
+        };
+        
+        // Omitted avg_gas comes from gas / bytes, and can't be derived without a byte count
+        execute(deps.as_mut(), mock_env(), info.clone(), run_msg("run_1", None, 400)).unwrap();
+        let run = find_run(deps.as_ref().storage, "run_1", None).unwrap().unwrap().1;
+        assert_eq!(run.avg_gas_per_byte, Uint128::new(25));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), run_msg("run_2", None, 0)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidGasValue(_)));
+        
+        // The default 10% tolerance applies without any config
+        execute(deps.as_mut(), mock_env(), info.clone(), run_msg("run_2", Some(27), 400)).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), run_msg("run_3", Some(40), 400)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidGasValue("avg_gas 40 is more than 1000 bps from gas / bytes = 25".to_string()));
+        
+        let tolerance = |bps: u64| ExecuteMsg::UpdateConfig {
+            max_message_size: None,
+            max_list_limit: None,
+            max_runs_limit: None,
+            max_messages: None,
+            eviction: None,
+            hex_tx_proofs: None,
+            strict_validation: None,
+            regression_threshold_bps: None,
+            min_gas_per_byte: None,
+            max_gas_per_byte: None,
+            avg_gas_tolerance_bps: Some(bps),
+            open_recording: None,
+            max_proof_bytes: None,
+            gas_budget: None,
+        };
+        
+        // Config can widen the tolerance, but not switch the check off
+        execute(deps.as_mut(), mock_env(), info.clone(), tolerance(10_000)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), run_msg("run_3", Some(40), 400)).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), run_msg("run_4", Some(60), 400)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidGasValue(_)));
+        for bps in [0, MAX_AVG_GAS_TOLERANCE_BPS + 1] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), tolerance(bps)).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidConfig(_)));
+        }
+        
+        // Configs stored while the tolerance was optional load with the default
+        let legacy: Config = from_json(br#"{"max_message_size":10000,"max_list_limit":30,"max_runs_limit":20,"avg_gas_tolerance_bps":null}"#).unwrap();
+        assert_eq!(legacy.avg_gas_tolerance_bps, DEFAULT_AVG_GAS_TOLERANCE_BPS);
+    }
+
+    #[test]
+    fn runs_by_time() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        // run_b and run_c share a second, run_a is recorded last but is the oldest after its update
+        for (run_id, timestamp) in [("run_d", 400), ("run_c", 200), ("run_b", 200), ("run_a", 900)] {
+            let mut env = mock_env();
+            env.block.time = cosmwasm_std::Timestamp::from_seconds(timestamp);
+            execute(deps.as_mut(), env, info.clone(), record_run_msg(run_id, 10, 10_000, 10, "osmosis-1")).unwrap();
+        }
+        let mut env = mock_env();
+        env.block.time = cosmwasm_std::Timestamp::from_seconds(100);
+        let mut msg = record_run_msg("run_a", 10, 10_000, 10, "osmosis-1");
+        if let ExecuteMsg::RecordTestRun { overwrite, .. } = &mut msg {
+            *overwrite = Some(true);
+        }
+        execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        
+        let by_time = |deps: Deps, from: Option<u64>, to: Option<u64>, limit: Option<u32>, ascending: Option<bool>| -> Vec<String> {
+            let msg = QueryMsg::GetRunsByTime { from, to, limit, ascending };
+            let res: TestRunsResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.runs.into_iter().map(|run| run.id).collect()
+        };
+        
+        assert_eq!(by_time(deps.as_ref(), None, None, None, None), vec!["run_a", "run_b", "run_c", "run_d"]);
+        assert_eq!(by_time(deps.as_ref(), None, None, None, Some(false)), vec!["run_d", "run_c", "run_b", "run_a"]);
+        // Both bounds are inclusive
+        assert_eq!(by_time(deps.as_ref(), Some(200), Some(400), None, None), vec!["run_b", "run_c", "run_d"]);
+        assert_eq!(by_time(deps.as_ref(), Some(200), Some(200), Some(1), Some(false)), vec!["run_c"]);
+        
+        let delete = ExecuteMsg::DeleteTestRun { run_id: "run_b".to_string(), chain: None };
+        execute(deps.as_mut(), mock_env(), info, delete).unwrap();
+        assert_eq!(by_time(deps.as_ref(), Some(101), None, None, None), vec!["run_c", "run_d"]);
+    }
+
+    #[test]
+    fn run_seq_numbers() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        for (run_id, chain) in [("run_b", "osmosis-1"), ("run_a", "osmosis-1"), ("run_a", "juno-1")] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 10, 10_000, 10, chain)).unwrap();
+        }
+        let by_seq = |deps: Deps, seq: u64| {
+            query(deps, mock_env(), QueryMsg::GetRunBySeq { seq }).map(|res| from_json::<TestRunResponse>(res).unwrap())
+        };
+        let run = by_seq(deps.as_ref(), 2).unwrap();
+        assert_eq!((run.id.as_str(), run.chain.as_str(), run.seq), ("run_a", "osmosis-1", 2));
+        assert_eq!(by_seq(deps.as_ref(), 3).unwrap().chain, "juno-1");
+        
+        // Overwriting keeps the number, deleting frees it without reuse
+        let mut msg = record_run_msg("run_b", 10, 20_000, 10, "osmosis-1");
+        if let ExecuteMsg::RecordTestRun { overwrite, .. } = &mut msg {
+            *overwrite = Some(true);
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(by_seq(deps.as_ref(), 1).unwrap().gas, Uint128::new(20_000));
+        
+        let delete = ExecuteMsg::DeleteTestRun { run_id: "run_b".to_string(), chain: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), delete).unwrap();
+        assert_eq!(by_seq(deps.as_ref(), 1).unwrap_err(), StdError::not_found("test run seq 1"));
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_b", 10, 10_000, 10, "osmosis-1")).unwrap();
+        assert_eq!(by_seq(deps.as_ref(), 4).unwrap().id, "run_b");
+    }
+
+    #[test]
+    fn list_messages_by_prefix() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        for height in [5, 12, 120, 13] {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::StoreMessage { content: "payload".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None };
+            execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        }
+        
+        let by_prefix = |prefix: &str| query(deps.as_ref(), mock_env(), QueryMsg::ListMessagesByPrefix { prefix: prefix.to_string(), limit: None });
+        let res: ListMessagesResponse = from_json(by_prefix("msg_12").unwrap()).unwrap();
+        let ids: Vec<String> = res.msgs.into_iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec!["msg_12", "msg_120"]);
+        
+        let res: ListMessagesResponse = from_json(by_prefix("msg_5").unwrap()).unwrap();
+        assert_eq!(res.count, 1);
+        assert_eq!(by_prefix("").unwrap_err(), StdError::generic_err("prefix cannot be empty"));
+    }
+
+    #[test]
+    fn open_recording() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        let anyone = mock_info("anyone", &[]);
+        
+        let err = execute(deps.as_mut(), mock_env(), anyone.clone(), record_run_msg("run_1", 10, 10_000, 10, "localnet")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        
+        let open = |open: bool| ExecuteMsg::UpdateConfig {
+            max_message_size: None,
+            max_list_limit: None,
+            max_runs_limit: None,
+            max_messages: None,
+            eviction: None,
+            hex_tx_proofs: None,
+            strict_validation: None,
+            regression_threshold_bps: None,
+            min_gas_per_byte: None,
+            max_gas_per_byte: None,
+            avg_gas_tolerance_bps: None,
+            open_recording: Some(open),
+            max_proof_bytes: None,
+            gas_budget: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), open(true)).unwrap();
+        let config: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert!(config.open_recording);
+        execute(deps.as_mut(), mock_env(), anyone.clone(), record_run_msg("run_1", 10, 10_000, 10, "localnet")).unwrap();
+        
+        execute(deps.as_mut(), mock_env(), info, open(false)).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), anyone, record_run_msg("run_2", 10, 10_000, 10, "localnet")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn test_runs_with_proofs() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        let mut msg = record_run_msg("run_1", 10, 10_000, 10, "osmosis-1");
+        if let ExecuteMsg::RecordTestRun { tx_hashes, .. } = &mut msg {
+            *tx_hashes = Some(vec!["tx1".to_string(), "tx2".to_string()]);
+        }
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        
+        let runs = |include_proofs: Option<bool>, limit: Option<u32>| {
+            let msg = QueryMsg::GetTestRuns { start_after: None, limit, chain: None, order: None, sort: None, tag: None, node_version: None, include_proofs };
+            query(deps.as_ref(), mock_env(), msg)
+        };
+        
+        // Off by default, no proof is serialized
+        let raw = runs(None, None).unwrap();
+        assert!(!String::from_utf8(raw.to_vec()).unwrap().contains("tx1"));
+        let res: TestRunsResponse = from_json(raw).unwrap();
+        assert_eq!(res.runs[0].tx_proofs, None);
+        
+        let res: TestRunsResponse = from_json(runs(Some(true), Some(5)).unwrap()).unwrap();
+        assert_eq!(res.runs[0].tx_proofs, Some(vec!["tx1".to_string(), "tx2".to_string()]));
+        
+        let err = runs(Some(true), Some(6)).unwrap_err();
+        assert_eq!(err, StdError::generic_err("limit can't exceed 5 with include_proofs"));
+    }
+
+    #[test]
+    fn proof_size_cap() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let config: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(config.max_proof_bytes, DEFAULT_MAX_PROOF_BYTES);
+        let cap = ExecuteMsg::UpdateConfig {
+            max_message_size: None,
+            max_list_limit: None,
+            max_runs_limit: None,
+            max_messages: None,
+            eviction: None,
+            hex_tx_proofs: None,
+            strict_validation: None,
+            regression_threshold_bps: None,
+            min_gas_per_byte: None,
+            max_gas_per_byte: None,
+            avg_gas_tolerance_bps: None,
+            open_recording: None,
+            max_proof_bytes: Some(10),
+            gas_budget: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), cap).unwrap();
+        
+        let with_proofs = |run_id: &str, proofs: &[&str]| {
+            let mut msg = record_run_msg(run_id, 10, 10_000, 10, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { tx_hashes, .. } = &mut msg {
+                *tx_hashes = Some(proofs.iter().map(|proof| proof.to_string()).collect());
+            }
+            msg
+        };
+        
+        // Exactly at the cap is accepted, one byte over is not
+        execute(deps.as_mut(), mock_env(), info.clone(), with_proofs("run_1", &["12345", "67890"])).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), with_proofs("run_2", &["12345", "678901"])).unwrap_err();
+        assert_eq!(err, ContractError::ProofTooLarge { size: 11, max: 10 });
+        
+        // Appending counts the proofs already stored
+        let append = ExecuteMsg::AppendTxProofs { run_id: "run_1".to_string(), proofs: vec!["a".to_string()], chain: None };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), append).unwrap_err();
+        assert_eq!(err, ContractError::ProofTooLarge { size: 11, max: 10 });
+        
+        // Joined proofs are trimmed and empty segments dropped before measuring
+        let msg: ExecuteMsg = from_json(
+            br#"{"record_test_run":{"run_id":"run_3","count":2,"gas":"1000","avg_gas":"10","chain":"osmosis-1","tx_proof":" 12345 , ,67890 ,"}}"#
+        ).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes[4].value, "2");
+    }
+
+    #[test]
+    fn store_response_data() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let store = ExecuteMsg::StoreMessage { content: "payload".to_string(), idempotency_key: Some("k".to_string()), gas_used: None, dedup: None, compress: None };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), store.clone()).unwrap();
+        let stored: StoreResult = from_json(res.data.unwrap()).unwrap();
+        assert_eq!(stored.id, format!("msg_{}", mock_env().block.height));
+        
+        // A replay returns the original id the same way
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), store).unwrap();
+        assert_eq!(from_json::<StoreResult>(res.data.unwrap()).unwrap(), stored);
+        
+        let fixed = ExecuteMsg::StoreFixedLength { content: "x".to_string(), length: 8, idempotency_key: None };
+        let res = execute(deps.as_mut(), mock_env(), info, fixed).unwrap();
+        let stored: StoreResult = from_json(res.data.unwrap()).unwrap();
+        assert_eq!(stored.id, format!("msg_{}_8", mock_env().block.height));
+    }
+
+    #[test]
+    fn gas_summary_smart_query() {
+        let mut contract = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(contract.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        execute(contract.as_mut(), mock_env(), info, record_run_msg("run_1", 10, 10_000, 10, "osmosis-1")).unwrap();
+        let expected = query_gas_summary_raw(contract.as_ref()).unwrap();
+        
+        // Route smart queries for the gas test contract to its query entry point
+        let mut caller = mock_dependencies();
+        caller.querier.update_wasm(move |request| match request {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "gas_test" => {
+                let res = from_json(msg).and_then(|msg| query(contract.as_ref(), mock_env(), msg));
+                SystemResult::Ok(res.into())
+            }
+            _ => SystemResult::Err(SystemError::NoSuchContract { addr: "unknown".to_string() }),
+        });
+        
+        let summary: GasSummary = caller
+            .as_ref()
+            .querier
+            .query_wasm_smart("gas_test", &QueryMsg::GetGasSummary { chain: None, window: None })
+            .unwrap();
+        assert_eq!(summary, expected);
+        assert_eq!(summary.total_gas, Uint128::new(10_000));
+    }
+
+    #[test]
+    fn run_duration_and_throughput() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        let now = mock_env().block.time.seconds();
+        
+        let timed = |run_id: &str, count: u64, start: Option<u64>, finish: Option<u64>| {
+            let mut msg = record_run_msg(run_id, count, 10_000, 10, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { started_at, finished_at, .. } = &mut msg {
+                *started_at = start;
+                *finished_at = finish;
+            }
+            msg
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), timed("run_1", 100, Some(now - 50), Some(now - 10))).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), timed("run_2", 20, Some(now - 5), Some(now - 5))).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), timed("run_3", 20, None, None)).unwrap();
+        
+        let get = |run_id: &str| -> TestRunResponse {
+            let msg = QueryMsg::GetTestRun { run_id: run_id.to_string(), include_proofs: None, chain: None };
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+        let run = get("run_1");
+        assert_eq!((run.duration_secs, run.msgs_per_sec), (Some(40), Some(2)));
+        // A zero duration has no rate, untimed runs report neither
+        let run = get("run_2");
+        assert_eq!((run.duration_secs, run.msgs_per_sec), (Some(0), None));
+        let run = get("run_3");
+        assert_eq!((run.started_at, run.duration_secs, run.msgs_per_sec), (None, None, None));
+        
+        let summary: GasSummary = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: Some(10) }).unwrap()).unwrap();
+        assert_eq!(summary.avg_msgs_per_sec, Some(2));
+        let exact: GasSummary = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummaryExact {}).unwrap()).unwrap();
+        assert_eq!(exact, summary);
+        
+        for msg in [timed("run_4", 1, Some(now - 5), Some(now - 6)), timed("run_4", 1, None, Some(now + 1))] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidRunMetadata(_)));
+        }
+    }
+
+    #[test]
+    fn record_test_run_batch() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let entry = |run_id: &str, chain: &str| -> RecordEntry {
+            let msg = format!(r#"{{"run_id":"{}","count":10,"gas":"10000","avg_gas":"10","chain":"{}"}}"#, run_id, chain);
+            from_json(msg.as_bytes()).unwrap()
+        };
+        
+        // Rejected batches write nothing
+        for (runs, reason) in [
+            (vec![entry("run_1", "osmosis-1"), entry(" ", "osmosis-1")], "Run ID cannot be empty"),
+            (vec![entry("run_1", "osmosis-1"), entry("run_1", "Osmosis-1")], "run_1 appears more than once for chain Osmosis-1 in the batch"),
+        ] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::RecordTestRunBatch { runs }).unwrap_err();
+            assert_eq!(err, ContractError::InvalidRunId(reason.to_string()));
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::RecordTestRunBatch { runs: vec![entry("run_1", "osmosis-1"); MAX_RECORD_BATCH + 1] },
+        ).unwrap_err();
+        assert_eq!(err, ContractError::LimitExceeded { requested: MAX_RECORD_BATCH as u64 + 1, max: MAX_RECORD_BATCH as u64 });
+        
+        // A budget crossed partway through is reported as it would be for single records
+        let budget = ExecuteMsg::UpdateConfig { max_message_size: None, max_list_limit: None, max_runs_limit: None, max_messages: None, eviction: None, hex_tx_proofs: None, strict_validation: None, regression_threshold_bps: None, min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: Some(Uint128::new(25_000)) };
+        execute(deps.as_mut(), mock_env(), info.clone(), budget).unwrap();
+        
+        let runs = vec![entry("run_1", "osmosis-1"), entry("run_2", "osmosis-1"), entry("run_1", "juno-1")];
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::RecordTestRunBatch { runs }).unwrap();
+        assert_eq!(res.attributes[1].value, "3");
+        let attribute_values = |key: &str| -> Vec<String> {
+            res.attributes.iter().filter(|attr| attr.key == key).map(|attr| attr.value.clone()).collect()
+        };
+        assert_eq!(attribute_values("action"), vec!["record_test_run_batch"]);
+        assert_eq!(attribute_values("run_id"), vec!["run_1", "run_2", "run_1"]);
+        assert_eq!(attribute_values("budget_exceeded"), vec!["false", "false", "true"]);
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "gas_budget_exceeded");
+        
+        let config: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(config.test_count, 3);
+        for (run_id, chain) in [("run_1", "osmosis-1"), ("run_2", "osmosis-1"), ("run_1", "juno-1")] {
+            let msg = QueryMsg::GetTestRun { run_id: run_id.to_string(), include_proofs: None, chain: Some(chain.to_string()) };
+            let run: TestRunResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(run.gas, Uint128::new(10_000));
+        }
+    }
+
+    #[test]
+    fn run_versions() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let versioned = |run_id: &str, version: Option<String>| {
+            let mut msg = record_run_msg(run_id, 10, 10_000, 10, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { node_version, .. } = &mut msg {
+                *node_version = version;
+            }
+            msg
+        };
+        
+        execute(deps.as_mut(), mock_env(), info.clone(), versioned("run_1", Some("wasmd v0.50.0".to_string()))).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), versioned("run_2", Some("wasmd v0.51.0".to_string()))).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), versioned("run_3", None)).unwrap();
+        
+        let run: TestRunResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRun { run_id: "run_1".to_string(), include_proofs: None, chain: None }).unwrap()
+        ).unwrap();
+        assert_eq!(run.node_version.as_deref(), Some("wasmd v0.50.0"));
+        assert_eq!(run.contract_version.as_deref(), Some(CONTRACT_VERSION));
+        
+        let msg = QueryMsg::GetTestRuns { start_after: None, limit: None, chain: None, order: None, sort: None, tag: None, node_version: Some("wasmd v0.51.0".to_string()), include_proofs: None };
+        let res: TestRunsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.runs.iter().map(|run| run.id.as_str()).collect::<Vec<_>>(), vec!["run_2"]);
+        
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), versioned("run_4", Some("v".repeat(MAX_NODE_VERSION_LENGTH + 1)))).unwrap_err();
+        assert!(err.to_string().contains("node_version is"));
+        let err = execute(deps.as_mut(), mock_env(), info, versioned("run_4", Some(" ".to_string()))).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRunMetadata("node_version is empty".to_string()));
+    }
 
     #[test]
-    fn proper_initialization() {
+    fn gas_summary_relative() {
         let mut deps = mock_dependencies();
-        let info = mock_info("creator", &coins(1000, "earth"));
-        let msg = InstantiateMsg {};
-
-        // Should succeed
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let mut msg = record_run_msg("run_1", 10, 12_000, 12, "osmosis-1");
+        if let ExecuteMsg::RecordTestRun { bytes, .. } = &mut msg {
+            *bytes = 1_000;
+        }
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        
+        // 12 gas per byte against a baseline of 8 is 150%
+        let msg = QueryMsg::GetGasSummaryRelative { baseline_gas_per_byte: Uint128::new(8) };
+        let res: RelativeGasSummary = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.summary.gas_per_byte, Uint128::new(12));
+        assert_eq!(res.baseline_gas_per_byte, Uint128::new(8));
+        assert_eq!(res.ratio_bps, Uint128::new(15_000));
+        
+        let msg = QueryMsg::GetGasSummaryRelative { baseline_gas_per_byte: Uint128::zero() };
+        let err = query(deps.as_ref(), mock_env(), msg).unwrap_err();
+        assert!(err.to_string().contains("baseline_gas_per_byte must be greater than zero"));
+    }
 
-        // Check state
-        let state = STATE.load(deps.as_ref().storage).unwrap();
-        assert_eq!(state.owner, "creator");
-        assert_eq!(state.test_run_count, 0);
-        assert_eq!(state.last_test_timestamp, None);
+    #[test]
+    fn run_message_ids_verified() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        for (height, content) in [(1, "a"), (2, "bb"), (3, "cccc")] {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::StoreMessage { content: content.to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None };
+            execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        }
+        
+        let covering = |run_id: &str, count: u64, ids: &[&str]| {
+            let mut msg = record_run_msg(run_id, count, 10_000, 10, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { message_ids, .. } = &mut msg {
+                *message_ids = Some(ids.iter().map(|id| id.to_string()).collect());
+            }
+            msg
+        };
+        
+        // All present
+        execute(deps.as_mut(), mock_env(), info.clone(), covering("run_1", 3, &["msg_1", "msg_2", "msg_3"])).unwrap();
+        let run: TestRunResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetTestRun { run_id: "run_1".to_string(), include_proofs: None, chain: None }).unwrap()
+        ).unwrap();
+        assert_eq!(run.coverage, Some(MessageCoverage { messages: 3, bytes: 7 }));
+        
+        // The first missing id is reported
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), covering("run_2", 3, &["msg_1", "msg_8", "msg_9"])).unwrap_err();
+        assert_eq!(err, ContractError::MessageNotFound { id: "msg_8".to_string() });
+        
+        // A retried message counted twice no longer matches
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), covering("run_2", 4, &["msg_1", "msg_2", "msg_3"])).unwrap_err();
+        assert_eq!(err, ContractError::MessageCountMismatch { count: 4, referenced: 3 });
+        let err = execute(deps.as_mut(), mock_env(), info, covering("run_2", 2, &["msg_1", "msg_1"])).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRunMetadata("message id msg_1 is listed more than once".to_string()));
     }
 
     #[test]
-    fn store_message() {
+    fn test_runs_newest_first_by_seq() {
         let mut deps = mock_dependencies();
-        let info = mock_info("creator", &coins(1000, "earth"));
-        let msg = InstantiateMsg {};
-        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        // Recorded out of lexical order
+        for (run_id, chain) in [("run_b", "osmosis-1"), ("run_c", "juno-1"), ("run_a", "osmosis-1"), ("run_10", "osmosis-1")] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 10, 10_000, 10, chain)).unwrap();
+        }
+        
+        let page = |deps: Deps, start_after: Option<String>, chain: Option<&str>, order: Option<&str>| -> TestRunsResponse {
+            let msg = QueryMsg::GetTestRuns {
+                start_after,
+                limit: Some(2),
+                chain: chain.map(str::to_string),
+                order: order.map(str::to_string),
+                sort: Some(RunSort::BySeq),
+                tag: None,
+                node_version: None,
+                include_proofs: None,
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let ids = |res: &TestRunsResponse| res.runs.iter().map(|run| run.id.clone()).collect::<Vec<_>>();
+        
+        let first = page(deps.as_ref(), None, None, None);
+        assert_eq!(ids(&first), vec!["run_10", "run_a"]);
+        assert_eq!(first.next_start_after.as_deref(), Some("3"));
+        
+        // A run recorded between pages doesn't shift the next one
+        execute(deps.as_mut(), mock_env(), info, record_run_msg("run_z", 10, 10_000, 10, "osmosis-1")).unwrap();
+        let second = page(deps.as_ref(), first.next_start_after, None, None);
+        assert_eq!(ids(&second), vec!["run_c", "run_b"]);
+        
+        let oldest: Vec<_> = ids(&page(deps.as_ref(), None, Some("osmosis-1"), Some("asc")));
+        assert_eq!(oldest, vec!["run_b", "run_a"]);
+        
+        let msg = QueryMsg::GetTestRuns { start_after: Some("run_a".to_string()), limit: None, chain: None, order: None, sort: Some(RunSort::BySeq), tag: None, node_version: None, include_proofs: None };
+        let err = query(deps.as_ref(), mock_env(), msg).unwrap_err();
+        assert!(err.to_string().contains("start_after must be a run seq"));
+    }
 
-        // Store valid message
-        let res = execute(
-            deps.as_mut(),
-            mock_env(),
-            info.clone(),
-            ExecuteMsg::StoreMessage { content: "test message".to_string() },
+    #[test]
+    fn gas_summary_large_values() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let big = |run_id: &str, gas: u128| {
+            let mut msg = record_run_msg(run_id, 1, gas, 10, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { avg_gas, bytes, .. } = &mut msg {
+                *avg_gas = None;
+                *bytes = 1_000;
+            }
+            msg
+        };
+        
+        // Squares of near-u64::MAX gas no longer saturate the std dev
+        let max = u64::MAX as u128;
+        execute(deps.as_mut(), mock_env(), info.clone(), big("run_1", max)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), big("run_2", max - 2_000)).unwrap();
+        for query_msg in [QueryMsg::GetGasSummary { chain: None, window: None }, QueryMsg::GetGasSummaryExact {}] {
+            let summary: GasSummary = from_json(query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            assert_eq!(summary.total_gas, Uint128::new(2 * max - 2_000));
+            assert_eq!(summary.gas_per_byte, Uint128::new((2 * max - 2_000) / 2_000));
+            assert_eq!(summary.gas_std_dev, Uint128::new(1_000));
+        }
+        
+        // Run byte estimates past u64 clamp instead of truncating
+        let mut msg = record_run_msg("run_3", 1, max * 4, 1, "juno-1");
+        if let ExecuteMsg::RecordTestRun { bytes, .. } = &mut msg {
+            *bytes = 0;
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let summary: GasSummary = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummaryExact {}).unwrap()).unwrap();
+        assert_eq!(summary.total_bytes, u64::MAX);
+        
+        // A total past u128 is an error, not a panic
+        let err = execute(deps.as_mut(), mock_env(), info, big("run_4", u128::MAX - max)).unwrap_err();
+        assert!(err.to_string().contains("gas summary overflow: total_gas is out of range"));
+        
+        // Aggregates stored before the widening still load
+        let legacy: Aggregates = from_json(
+            br#"{"run_count":1,"total_messages":1,"total_gas":"100","total_bytes":50,"gas_squares":"10000","weighted_gas":"100"}"#
         ).unwrap();
-        assert_eq!(res.attributes.len(), 3);
+        assert_eq!(legacy.total_bytes, Uint128::new(50));
+    }
 
-        // Test too large message
-        let large_msg = "x".repeat((MAX_MESSAGE_SIZE + 1) as usize);
-        let err = execute(
-            deps.as_mut(),
-            mock_env(),
-            info,
-            ExecuteMsg::StoreMessage { content: large_msg },
-        ).unwrap_err();
+    #[test]
+    fn gas_summary_runs_excluded_from_bytes() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
         
-        // Should return MessageTooLarge error
-        match err {
-            ContractError::MessageTooLarge { size, max } => {
-                assert_eq!(size, MAX_MESSAGE_SIZE + 1);
-                assert_eq!(max, MAX_MESSAGE_SIZE);
-            },
-            e => panic!("unexpected error: {:?}", e),
+        // No bytes and a zero average leave nothing to estimate from
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 10, 10_000, 0, "osmosis-1")).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_2", 10, 10_000, 10, "osmosis-1")).unwrap();
+        
+        for query_msg in [QueryMsg::GetGasSummary { chain: None, window: None }, QueryMsg::GetGasSummaryExact {}] {
+            let summary: GasSummary = from_json(query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            assert_eq!(summary.runs_excluded_from_bytes, 1);
+            assert_eq!(summary.total_bytes, 1_000);
         }
+        
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::DeleteTestRun { run_id: "run_1".to_string(), chain: None }).unwrap();
+        let summary: GasSummary = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()).unwrap();
+        assert_eq!(summary.runs_excluded_from_bytes, 0);
     }
 
     #[test]
-    fn fixed_length_message() {
+    fn gas_summary_weighted_by_bytes() {
         let mut deps = mock_dependencies();
-        let info = mock_info("creator", &coins(1000, "earth"));
-        let msg = InstantiateMsg {};
-        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let sized = |run_id: &str, count: u64, gas: u128, run_bytes: u64| {
+            let mut msg = record_run_msg(run_id, count, gas, gas / run_bytes as u128, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { bytes, .. } = &mut msg {
+                *bytes = run_bytes;
+            }
+            msg
+        };
+        
+        // A tiny run at 100 gas per byte next to a large one at 10
+        execute(deps.as_mut(), mock_env(), info.clone(), sized("run_small", 1, 1_000, 10)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), sized("run_large", 99, 1_000_000, 100_000)).unwrap();
+        
+        let summary: GasSummary = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()).unwrap();
+        // The naive mean of the per-run averages would be (100 + 10) / 2 = 55
+        assert_eq!(summary.gas_per_byte, Uint128::new(1_001_000 / 100_010));
+        assert_eq!(summary.gas_per_byte, Uint128::new(10));
+        assert!(!summary.estimated);
+        // Per message, 1_001_000 / 100 rather than the mean of 1000 and 10101
+        assert_eq!(summary.avg_gas, Uint128::new(10_010));
+        
+        // A run without reported bytes makes the figure an estimate
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_unsized", 10, 20_000, 20, "osmosis-1")).unwrap();
+        let summary: GasSummary = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummaryExact {}).unwrap()).unwrap();
+        assert!(summary.estimated);
+        
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::DeleteTestRun { run_id: "run_unsized".to_string(), chain: None }).unwrap();
+        let summary: GasSummary = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: None }).unwrap()).unwrap();
+        assert!(!summary.estimated);
+    }
 
-        // Test padding (content shorter than target)
-        let res = execute(
-            deps.as_mut(),
-            mock_env(),
-            info.clone(),
-            ExecuteMsg::StoreFixedLength { 
-                content: "test".to_string(), 
-                length: 10
-            },
-        ).unwrap();
-        assert_eq!(res.attributes.len(), 3);
+    #[test]
+    fn run_time_bounds() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
         
-        // Check the message was stored correctly
-        let msg_id = res.attributes[1].value.clone(); // id attribute
-        let query_res: MessageResponse = from_binary(
-            &query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: msg_id }).unwrap()
-        ).unwrap();
-        assert_eq!(query_res.length, 10);
-        assert_eq!(query_res.content, "test      "); // 4 chars + 6 spaces
+        let bounds = |deps: Deps| -> RunTimeBoundsResponse {
+            from_json(query(deps, mock_env(), QueryMsg::GetRunTimeBounds {}).unwrap()).unwrap()
+        };
+        assert_eq!(bounds(deps.as_ref()), RunTimeBoundsResponse { oldest: None, newest: None, run_count: 0 });
+        
+        for (run_id, time) in [("run_b", 2_000), ("run_a", 1_000), ("run_c", 1_500)] {
+            let mut env = mock_env();
+            env.block.time = cosmwasm_std::Timestamp::from_seconds(time);
+            execute(deps.as_mut(), env, info.clone(), record_run_msg(run_id, 10, 10_000, 10, "osmosis-1")).unwrap();
+        }
+        assert_eq!(bounds(deps.as_ref()), RunTimeBoundsResponse { oldest: Some(1_000), newest: Some(2_000), run_count: 3 });
+        
+        // Without aggregates the runs are counted instead
+        AGGREGATES.remove(deps.as_mut().storage);
+        assert_eq!(bounds(deps.as_ref()).run_count, 3);
+    }
 
-        // Test truncation (content longer than target)
-        let res = execute(
-            deps.as_mut(),
-            mock_env(),
-            info,
-            ExecuteMsg::StoreFixedLength { 
-                content: "this is a longer test".to_string(), 
-                length: 7
-            },
-        ).unwrap();
+    #[test]
+    fn gas_summary_spread() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
         
-        let msg_id = res.attributes[1].value.clone();
-        let query_res: MessageResponse = from_binary(
-            &query(deps.as_ref(), mock_env(), QueryMsg::GetMessage { id: msg_id }).unwrap()
-        ).unwrap();
-        assert_eq!(query_res.length, 7);
-        assert_eq!(query_res.content, "this is"); // truncated to 7 chars
+        let summary = |deps: Deps, window: Option<u32>| -> GasSummary {
+            from_json(query(deps, mock_env(), QueryMsg::GetGasSummary { chain: None, window }).unwrap()).unwrap()
+        };
+        
+        // A single run has no spread
+        execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_0", 10, 10_000, 70, "osmosis-1")).unwrap();
+        let single = summary(deps.as_ref(), Some(10));
+        assert_eq!((single.stddev_gas_per_byte, single.p50, single.p90, single.p99), (None, None, None, None));
+        
+        // avg_gas 10, 20, ..., 100 recorded out of order, run_0 is replaced by the 70
+        for (i, avg_gas) in [70u128, 30, 100, 10, 60, 90, 20, 50, 80, 40].iter().enumerate() {
+            let mut msg = record_run_msg(&format!("run_{}", i), 10, 10_000, *avg_gas, "osmosis-1");
+            if let ExecuteMsg::RecordTestRun { overwrite, .. } = &mut msg {
+                *overwrite = Some(true);
+            }
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        
+        // Nearest rank over 10 values: p50 is the 5th, p90 the 9th, p99 the 10th;
+        // mean 55, variance 825, std dev floor(28.7) = 28
+        // The cached summary leaves the spread out unless given a window
+        let cached = summary(deps.as_ref(), None);
+        assert_eq!((cached.stddev_gas_per_byte, cached.p50, cached.p90, cached.p99), (None, None, None, None));
+        for res in [summary(deps.as_ref(), Some(10)), from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummaryExact {}).unwrap()).unwrap()] {
+            assert_eq!(res.p50, Some(Uint128::new(50)));
+            assert_eq!(res.p90, Some(Uint128::new(90)));
+            assert_eq!(res.p99, Some(Uint128::new(100)));
+            assert_eq!(res.stddev_gas_per_byte, Some(Uint128::new(28)));
+        }
+        
+        // The newest 3 runs by seq are 50, 80 and 40, run_0 kept its first seq when replaced
+        // mean 56.7, variance floor(288.9) = 288, std dev floor(16.97) = 16
+        let windowed = summary(deps.as_ref(), Some(3));
+        assert_eq!(windowed.p50, Some(Uint128::new(50)));
+        assert_eq!(windowed.p90, Some(Uint128::new(80)));
+        assert_eq!(windowed.stddev_gas_per_byte, Some(Uint128::new(16)));
+        
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary { chain: None, window: Some(0) }).unwrap_err();
+        assert!(err.to_string().contains("window must be greater than zero"));
     }
 
     #[test]
-    fn test_clear_data() {
+    fn length_histogram() {
         let mut deps = mock_dependencies();
-        let info = mock_info("creator", &coins(1000, "earth"));
-        let msg = InstantiateMsg {};
-        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        // 10 and 20 sit exactly on bin edges
+        for (height, length) in [(1, 9usize), (2, 10), (3, 19), (4, 20)] {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::StoreMessage { content: "x".repeat(length), idempotency_key: None, gas_used: None, dedup: None, compress: None };
+            execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        }
+        
+        let histogram = |deps: Deps, bucket_size: u64, max_scan: Option<u32>| -> StdResult<LengthHistogramResponse> {
+            query(deps, mock_env(), QueryMsg::GetLengthHistogram { bucket_size, max_scan }).map(|res| from_json(res).unwrap())
+        };
+        
+        let res = histogram(deps.as_ref(), 10, None).unwrap();
+        assert_eq!(res.scanned, 4);
+        assert_eq!(res.buckets, vec![
+            LengthBucket { start: 0, end: 10, count: 1, total_bytes: 9 },
+            LengthBucket { start: 10, end: 20, count: 2, total_bytes: 29 },
+            LengthBucket { start: 20, end: 30, count: 1, total_bytes: 20 },
+        ]);
+        
+        let res = histogram(deps.as_ref(), 10, Some(2)).unwrap();
+        assert_eq!(res.scanned, 2);
+        assert_eq!(res.buckets.iter().map(|bucket| bucket.count).sum::<u64>(), 2);
+        
+        assert_eq!(histogram(deps.as_ref(), MAX_MESSAGE_SIZE, None).unwrap().buckets.len(), 1);
+        for bucket_size in [0, MAX_MESSAGE_SIZE + 1] {
+            let err = histogram(deps.as_ref(), bucket_size, None).unwrap_err();
+            assert!(err.to_string().contains("bucket_size must be between 1 and"));
+        }
+        
+        // A lowered max_message_size bounds bucket_size and folds longer messages into the last bin
+        let msg = ExecuteMsg::UpdateConfig { max_message_size: Some(15), max_list_limit: None, max_runs_limit: None, max_messages: None, eviction: None, hex_tx_proofs: None, strict_validation: None, regression_threshold_bps: None, min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let err = histogram(deps.as_ref(), 16, None).unwrap_err();
+        assert!(err.to_string().contains("bucket_size must be between 1 and 15"));
+        assert_eq!(histogram(deps.as_ref(), 10, None).unwrap().buckets, vec![
+            LengthBucket { start: 0, end: 10, count: 1, total_bytes: 9 },
+            LengthBucket { start: 10, end: 20, count: 3, total_bytes: 49 },
+        ]);
+    }
 
-        // Store some test data
+    #[test]
+    fn run_id_charset() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        let max_length = "r".repeat(MAX_RUN_ID_LENGTH);
+        for run_id in ["Run_2024-01-a", max_length.as_str()] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 10, 10_000, 10, "osmosis-1")).unwrap();
+        }
+        
+        let too_long = "r".repeat(MAX_RUN_ID_LENGTH + 1);
+        for (run_id, reason) in [
+            ("run,1", "Run ID contains ',', only letters, digits, '_' and '-' are allowed".to_string()),
+            ("run\n1", "Run ID contains '\\n', only letters, digits, '_' and '-' are allowed".to_string()),
+            (too_long.as_str(), format!("Run ID is {} bytes, maximum is {}", MAX_RUN_ID_LENGTH + 1, MAX_RUN_ID_LENGTH)),
+        ] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 10, 10_000, 10, "osmosis-1")).unwrap_err();
+            assert_eq!(err, ContractError::InvalidRunId(reason));
+        }
+        
+        // The batch checks every entry before recording any
+        let entry: RecordEntry = from_json(br#"{"run_id":"run,2","count":10,"gas":"10000","avg_gas":"10","chain":"osmosis-1"}"#).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::RecordTestRunBatch { runs: vec![entry] }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRunId(_)));
+    }
+
+    #[test]
+    fn gas_budget_warning() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
         execute(
             deps.as_mut(),
             mock_env(),
             info.clone(),
-            ExecuteMsg::StoreMessage { content: "test1".to_string() },
+            ExecuteMsg::UpdateConfig { max_message_size: None, max_list_limit: None, max_runs_limit: None, max_messages: None, eviction: None, hex_tx_proofs: None, strict_validation: None, regression_threshold_bps: None, min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: Some(Uint128::new(25_000)) },
         ).unwrap();
         
+        let budget_exceeded = |res: &Response| {
+            res.attributes.iter().find(|attr| attr.key == "budget_exceeded").map(|attr| attr.value.clone())
+        };
+        
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_1", 10, 10_000, 10, "osmosis-1")).unwrap();
+        assert_eq!(budget_exceeded(&res).as_deref(), Some("false"));
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_2", 10, 10_000, 10, "osmosis-1")).unwrap();
+        assert_eq!(budget_exceeded(&res).as_deref(), Some("false"));
+        
+        // Crossing the budget doesn't fail the record, the event is only on the crossing run
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_3", 10, 10_000, 10, "osmosis-1")).unwrap();
+        assert_eq!(budget_exceeded(&res).as_deref(), Some("true"));
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "gas_budget_exceeded");
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg("run_4", 10, 10_000, 10, "osmosis-1")).unwrap();
+        assert_eq!(budget_exceeded(&res).as_deref(), Some("true"));
+        assert!(res.events.is_empty());
+        
+        let config: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(config.gas_budget, Some(Uint128::new(25_000)));
+        assert_eq!(config.cumulative_gas, Uint128::new(40_000));
+        
+        // Without a budget the attribute is left out
         execute(
             deps.as_mut(),
             mock_env(),
             info.clone(),
-            ExecuteMsg::StoreMessage { content: "test2".to_string() },
+            ExecuteMsg::UpdateConfig { max_message_size: None, max_list_limit: None, max_runs_limit: None, max_messages: None, eviction: None, hex_tx_proofs: None, strict_validation: None, regression_threshold_bps: None, min_gas_per_byte: None, max_gas_per_byte: None, avg_gas_tolerance_bps: None, open_recording: None, max_proof_bytes: None, gas_budget: Some(Uint128::zero()) },
         ).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info, record_run_msg("run_5", 10, 10_000, 10, "osmosis-1")).unwrap();
+        assert_eq!(budget_exceeded(&res), None);
+    }
 
-        // Record a test run
-        execute(
-            deps.as_mut(),
-            mock_env(),
-            info.clone(),
-            ExecuteMsg::RecordTestRun { 
-                run_id: "test_run_1".to_string(),
-                count: 2,
-                gas: Uint128::new(100000),
-                avg_gas: Uint128::new(50000),
-                chain: "test-chain".to_string(),
-                tx_proof: Some("tx1,tx2".to_string())
-            },
-        ).unwrap();
+    #[test]
+    fn export_runs_csv_round_trip() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        for (run_id, chain) in [("run_a", "osmosis-1"), ("run_b", "odd,\"chain\""), ("run_c", "juno-1")] {
+            execute(deps.as_mut(), mock_env(), info.clone(), record_run_msg(run_id, 10, 10_000, 10, chain)).unwrap();
+        }
+        
+        // Splits one CSV line, undoing the quoting
+        let parse_row = |line: &str| -> Vec<String> {
+            let (mut fields, mut field, mut quoted, mut chars) = (vec![], String::new(), false, line.chars().peekable());
+            while let Some(c) = chars.next() {
+                match (c, quoted) {
+                    ('"', true) if chars.peek() == Some(&'"') => {
+                        field.push('"');
+                        chars.next();
+                    }
+                    ('"', _) => quoted = !quoted,
+                    (',', false) => fields.push(std::mem::take(&mut field)),
+                    _ => field.push(c),
+                }
+            }
+            fields.push(field);
+            fields
+        };
+        
+        let mut start_after = None;
+        let mut pages = 0;
+        loop {
+            let csv: ExportRunsCsvResponse = from_json(
+                query(deps.as_ref(), mock_env(), QueryMsg::ExportRunsCsv { start_after: start_after.clone(), limit: Some(2) }).unwrap()
+            ).unwrap();
+            let msg = QueryMsg::GetTestRuns { start_after: start_after.clone(), limit: Some(2), chain: None, order: Some("asc".to_string()), sort: None, tag: None, node_version: None, include_proofs: None };
+            let runs: TestRunsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            
+            let mut lines = csv.csv.lines();
+            assert_eq!(lines.next(), Some("run_id,timestamp,chain,count,total_gas,avg_gas_per_byte,tx_count"));
+            let rows: Vec<Vec<String>> = lines.map(parse_row).collect();
+            let expected: Vec<Vec<String>> = runs.runs.iter().map(|run| vec![
+                run.id.clone(),
+                run.time.to_string(),
+                run.chain.clone(),
+                run.count.to_string(),
+                run.gas.to_string(),
+                run.avg_gas.to_string(),
+                run.tx_count.to_string(),
+            ]).collect();
+            assert_eq!(rows, expected);
+            assert_eq!(csv.next_start_after, runs.next_start_after);
+            
+            pages += 1;
+            start_after = csv.next_start_after;
+            if start_after.is_none() {
+                break;
+            }
+        }
+        assert_eq!(pages, 2);
+    }
 
-        // Test unauthorized clear
-        let unauth_info = mock_info("someone_else", &coins(1000, "earth"));
-        let err = execute(
-            deps.as_mut(),
-            mock_env(),
-            unauth_info,
-            ExecuteMsg::ClearData {},
-        ).unwrap_err();
+    #[test]
+    fn gas_series() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
         
-        // Should return Unauthorized error
-        match err {
-            ContractError::Unauthorized {} => {},
-            e => panic!("unexpected error: {:?}", e),
+        // Two runs share second 200, run_z has no avg_gas to plot
+        for (run_id, time, avg_gas, chain) in [
+            ("run_a", 100, 10, "osmosis-1"),
+            ("run_b", 200, 20, "juno-1"),
+            ("run_c", 200, 30, "osmosis-1"),
+            ("run_z", 250, 0, "osmosis-1"),
+            ("run_d", 300, 40, "osmosis-1"),
+        ] {
+            let mut env = mock_env();
+            env.block.time = cosmwasm_std::Timestamp::from_seconds(time);
+            execute(deps.as_mut(), env, info.clone(), record_run_msg(run_id, 10, 10_000, avg_gas, chain)).unwrap();
+        }
+        
+        let series = |deps: Deps, chain: Option<&str>, from: Option<u64>, to: Option<u64>, limit: Option<u32>, start_after: Option<SeriesCursor>| -> GasSeriesResponse {
+            let msg = QueryMsg::GetGasSeries { chain: chain.map(str::to_string), from, to, limit, start_after };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let point = |time: u64, gas_per_byte: u128, chain: &str| GasPoint { time, gas_per_byte: Uint128::new(gas_per_byte), chain: chain.to_string() };
+        
+        // The window edges are inclusive, the zero run is skipped
+        let res = series(deps.as_ref(), None, Some(200), Some(300), None, None);
+        assert_eq!(res.points, vec![point(200, 20, "juno-1"), point(200, 30, "osmosis-1"), point(300, 40, "osmosis-1")]);
+        assert_eq!(res.next, None);
+        
+        // A page ending inside second 200 continues with the other run of that second
+        let first = series(deps.as_ref(), None, None, None, Some(2), None);
+        assert_eq!(first.points, vec![point(100, 10, "osmosis-1"), point(200, 20, "juno-1")]);
+        let second = series(deps.as_ref(), None, None, None, Some(2), first.next);
+        assert_eq!(second.points, vec![point(200, 30, "osmosis-1"), point(300, 40, "osmosis-1")]);
+        let last = series(deps.as_ref(), None, None, None, Some(2), second.next);
+        assert!(last.points.is_empty());
+        assert_eq!(last.next, None);
+        
+        let res = series(deps.as_ref(), Some("Juno-1"), None, None, None, None);
+        assert_eq!(res.points, vec![point(200, 20, "juno-1")]);
+        
+        // Index entries filtered out by chain still count toward the scan cap
+        for i in 0..MAX_SERIES_SCAN {
+            let run_id = format!("noise_{:05}", i);
+            RUN_TIMES.save(deps.as_mut().storage, (150, "noise-1", &run_id), &Empty {}).unwrap();
         }
+        let first = series(deps.as_ref(), Some("osmosis-1"), None, None, None, None);
+        assert_eq!(first.points, vec![point(100, 10, "osmosis-1")]);
+        let last_noise = format!("noise_{:05}", MAX_SERIES_SCAN - 2);
+        assert_eq!(first.next, Some(SeriesCursor { time: 150, chain: "noise-1".to_string(), run_id: last_noise }));
+        let second = series(deps.as_ref(), Some("osmosis-1"), None, None, None, first.next);
+        assert_eq!(second.points, vec![point(200, 30, "osmosis-1"), point(300, 40, "osmosis-1")]);
+        assert_eq!(second.next, None);
+    }
 
-        // Test authorized clear
-        let res = execute(
-            deps.as_mut(),
-            mock_env(),
-            info,
-            ExecuteMsg::ClearData {},
-        ).unwrap();
-        assert_eq!(res.attributes.len(), 2);
+    #[test]
+    fn concat_messages() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        
+        for (height, content) in [(1, "a"), (2, "b"), (3, "c")] {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::StoreMessage { content: content.to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None };
+            execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        }
+        
+        let concat = |deps: Deps, separator: Option<&str>, limit: Option<u32>| -> ConcatMessagesResponse {
+            let msg = QueryMsg::ConcatMessages { separator: separator.map(str::to_string), limit };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        
+        assert_eq!(concat(deps.as_ref(), Some(","), None), ConcatMessagesResponse { content: "a,b,c".to_string(), count: 3 });
+        assert_eq!(concat(deps.as_ref(), None, None).content, "abc");
+        assert_eq!(concat(deps.as_ref(), Some(","), Some(2)).content, "a,b");
+    }
 
-        // Verify data was cleared - count should be 0
-        let config: ConfigResponse = from_binary(
-            &query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
-        ).unwrap();
-        assert_eq!(config.test_count, 0);
+    #[test]
+    fn paused_blocks_gated_actions() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        assert_eq!(ensure_not_paused(deps.as_ref().storage), Ok(()));
 
-        // Verify gas summary is reset
-        let summary: GasSummary = from_binary(
-            &query(deps.as_ref(), mock_env(), QueryMsg::GetGasSummary {}).unwrap()
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::SetPaused { paused: true }).unwrap();
+        assert_eq!(ensure_not_paused(deps.as_ref().storage), Err(ContractError::Paused {}));
+
+        let gated = vec![
+            ExecuteMsg::StoreMessage { content: "test".to_string(), idempotency_key: None, gas_used: None, dedup: None, compress: None },
+            ExecuteMsg::StoreFixedLength { content: "ab".to_string(), length: 8, idempotency_key: None },
+            record_run_msg("run_1", 1, 100, 100, "chain-1"),
+        ];
+        for msg in gated.iter() {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::Paused {});
+        }
+        let config: ConfigResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()
         ).unwrap();
-        assert_eq!(summary.msg_count, 0);
-        assert_eq!(summary.total_gas, Uint128::zero());
+        assert_eq!(config.message_count, 0);
+
+        // Each action goes through once the pause is lifted
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::SetPaused { paused: false }).unwrap();
+        for (height, msg) in gated.into_iter().enumerate() {
+            let mut env = mock_env();
+            env.block.height += height as u64;
+            execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        }
+    }
+
+    #[test]
+    fn idempotency_key_dropped_with_message() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        let store = |key: &str| ExecuteMsg::StoreMessage {
+            content: "test".to_string(),
+            idempotency_key: Some(key.to_string()),
+            gas_used: None,
+            dedup: None,
+            compress: None,
+        };
+
+        let mut env = mock_env();
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), store("key1")).unwrap();
+        let first_id = res.attributes[1].value.clone();
+
+        // Once the message is deleted a retry writes it again instead of replaying a missing id
+        execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::DeleteRange { start_after: None, limit: 10 }).unwrap();
+        assert!(IDEMPOTENCY_KEYS.may_load(deps.as_ref().storage, "key1").unwrap().is_none());
+        env.block.height += 1;
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), store("key1")).unwrap();
+        let second_id = res.attributes[1].value.clone();
+        assert_ne!(second_id, first_id);
+        assert!(res.attributes.iter().all(|attr| attr.key != "idempotent_replay"));
+        assert!(MESSAGES.has(deps.as_ref().storage, &second_id));
+
+        // A same-height overwrite drops the key of the message it replaced
+        execute(deps.as_mut(), env.clone(), info.clone(), store("key2")).unwrap();
+        assert!(IDEMPOTENCY_KEYS.may_load(deps.as_ref().storage, "key1").unwrap().is_none());
+        assert_eq!(IDEMPOTENCY_KEYS.load(deps.as_ref().storage, "key2").unwrap(), second_id);
+
+        // A key left behind without its message is also treated as unused
+        IDEMPOTENCY_KEYS.save(deps.as_mut().storage, "stale", &"msg_1".to_string()).unwrap();
+        env.block.height += 1;
+        let res = execute(deps.as_mut(), env, info.clone(), store("stale")).unwrap();
+        assert!(res.attributes.iter().all(|attr| attr.key != "idempotent_replay"));
+
+        let too_long = "k".repeat(MAX_IDEMPOTENCY_KEY_LENGTH + 1);
+        let err = execute(deps.as_mut(), mock_env(), info, store(&too_long)).unwrap_err();
+        assert_eq!(err, ContractError::IdempotencyKeyTooLong {
+            length: MAX_IDEMPOTENCY_KEY_LENGTH as u64 + 1,
+            max: MAX_IDEMPOTENCY_KEY_LENGTH as u64,
+        });
+    }
+
+    #[test]
+    fn gas_curve_overflow_is_an_error() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+
+        let mut msg = record_run_msg("run_a", 1, 1000, 10, "test-chain");
+        if let ExecuteMsg::RecordTestRun { byte_length, .. } = &mut msg {
+            *byte_length = Some(100);
+        }
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Two stored runs at the same length whose rates can't be summed
+        let mut run = TEST_RUNS.load(deps.as_ref().storage, ("test-chain", "run_a")).unwrap();
+        run.avg_gas_per_byte = Uint128::MAX;
+        TEST_RUNS.save(deps.as_mut().storage, ("test-chain", "run_a"), &run).unwrap();
+        TEST_RUNS.save(deps.as_mut().storage, ("test-chain", "run_b"), &run).unwrap();
+
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::GetGasCurve {}).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+    }
+
+    #[test]
+    fn metric_delta_saturates() {
+        let res = metric_delta(Uint128::new(100), Uint128::new(150));
+        assert_eq!((res.delta, res.change_bps), (Int128::new(50), Int128::new(5000)));
+
+        // Values past i128::MAX clamp rather than wrap to a negative delta
+        let res = metric_delta(Uint128::zero(), Uint128::MAX);
+        assert_eq!(res.delta, Int128::MAX);
+        let res = metric_delta(Uint128::MAX, Uint128::new(1));
+        assert_eq!(res.delta, Int128::new(-i128::MAX));
+        assert_eq!(res.change_bps, Int128::new(-9999));
+    }
+
+    #[test]
+    fn batched_clear_keeps_live_idempotency_keys() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(1000, "earth"));
+        instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg::default()).unwrap();
+        let store = |key: &str| ExecuteMsg::StoreMessage {
+            content: key.to_string(),
+            idempotency_key: Some(key.to_string()),
+            gas_used: None,
+            dedup: Some(true),
+            compress: None,
+        };
+
+        for (height, key) in [(1, "key1"), (2, "key2"), (3, "key3")] {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(deps.as_mut(), env, info.clone(), store(key)).unwrap();
+        }
+
+        // One message per batch, the other two keep their keys and hashes
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClearData { limit: Some(1) }).unwrap();
+        assert!(res.attributes.iter().any(|attr| attr.key == "complete" && attr.value == "false"));
+        assert!(IDEMPOTENCY_KEYS.may_load(deps.as_ref().storage, "key1").unwrap().is_none());
+        assert_eq!(CONTENT_HASHES.keys(deps.as_ref().storage, None, None, cosmwasm_std::Order::Ascending).count(), 3);
+
+        let mut env = mock_env();
+        env.block.height = 10;
+        let res = execute(deps.as_mut(), env, info.clone(), store("key3")).unwrap();
+        assert_eq!(res.attributes[1].value, "msg_3");
+        assert_eq!(res.attributes[2].key, "idempotent_replay");
+
+        // Later batches finish the messages, then sweep every leftover map
+        loop {
+            let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClearData { limit: Some(1) }).unwrap();
+            if res.attributes.iter().any(|attr| attr.key == "complete" && attr.value == "true") {
+                break;
+            }
+        }
+        let storage = deps.as_ref().storage;
+        assert_eq!(IDEMPOTENCY_KEYS.keys(storage, None, None, cosmwasm_std::Order::Ascending).count(), 0);
+        assert_eq!(IDEMPOTENCY_IDS.keys(storage, None, None, cosmwasm_std::Order::Ascending).count(), 0);
+        assert_eq!(CONTENT_HASHES.keys(storage, None, None, cosmwasm_std::Order::Ascending).count(), 0);
     }
 }
\ No newline at end of file